@@ -1,9 +1,12 @@
 use crate::{
-    datetime::{eodhd_serde_datetime, EODHDDate},
-    env_eodhd_token, BASE_URL,
+    datetime::{eodhd_serde_datetime, EODHDDate, EODHDTimestamped},
+    eodhd_opt_string_float,
+    rate_limit::EODHDEndpoint,
+    EODHDClient, EODHDError,
 };
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum EODHDEconomicEventComparison {
@@ -15,14 +18,13 @@ pub enum EODHDEconomicEventComparison {
     YOY,
 }
 
-impl ToString for EODHDEconomicEventComparison {
-    fn to_string(&self) -> String {
-        (match self {
+impl Display for EODHDEconomicEventComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             EODHDEconomicEventComparison::MOM => "mom",
             EODHDEconomicEventComparison::QOQ => "qoq",
             EODHDEconomicEventComparison::YOY => "yoy",
         })
-        .to_string()
     }
 }
 
@@ -34,35 +36,45 @@ pub struct EODHDEconomicEvent {
     pub country: String,
     #[serde(with = "eodhd_serde_datetime")]
     pub date: NaiveDateTime,
-    pub actual: f64,
-    pub previous: f64,
-    pub estimate: f64,
-    pub change: f64,
-    pub change_percentage: f64,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub actual: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub previous: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub estimate: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_percentage: Option<f64>,
 }
 
-pub async fn get_economic_events(from: EODHDDate, to: EODHDDate) -> Vec<EODHDEconomicEvent> {
-    let token = env_eodhd_token();
-    let url = format!(
-        "{base_url}/economic-events?api_token={token}&from={from}&to={to}",
-        base_url = BASE_URL,
-        token = token,
-        from = NaiveDate::from(from),
-        to = NaiveDate::from(to),
-    );
-    let request = reqwest::get(url.to_owned()).await;
-    if request.is_err() {
-        log::error!(
-            "REQUEST TO EODHD FAILED \n{:?}\n with {:?}",
-            url,
-            request.err()
-        );
-        panic!();
+/// `date` is UTC
+impl EODHDTimestamped for EODHDEconomicEvent {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        Some(Utc.from_utc_datetime(&self.date))
     }
-    let parsed_events = request.unwrap().json::<Vec<EODHDEconomicEvent>>().await;
-    if parsed_events.is_err() {
-        log::error!("CONVERTING EODHD RESPONSE TO TEXT FAILED");
-        panic!("{:?}", parsed_events.err());
+}
+
+impl EODHDClient {
+    pub async fn get_economic_events(
+        &self,
+        from: EODHDDate,
+        to: EODHDDate,
+    ) -> Result<Vec<EODHDEconomicEvent>, EODHDError> {
+        let query = [
+            ("from", NaiveDate::from(from).to_string()),
+            ("to", NaiveDate::from(to).to_string()),
+        ];
+        self.get_json(EODHDEndpoint::EconomicEvents, "economic-events", &query)
+            .await
     }
-    parsed_events.unwrap()
+}
+
+/// Shorthand for [EODHDClient::get_economic_events]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_economic_events(
+    from: EODHDDate,
+    to: EODHDDate,
+) -> Result<Vec<EODHDEconomicEvent>, EODHDError> {
+    EODHDClient::from_env()?.get_economic_events(from, to).await
 }
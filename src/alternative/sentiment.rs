@@ -1,6 +1,9 @@
 use crate::{
     datetime::{eodhd_serde_date, EODHDDate},
-    env_eodhd_token, BASE_URL,
+    eodhd_string_float, eodhd_string_int,
+    rate_limit::EODHDEndpoint,
+    ticker::{parse_ticker, EODHDTicker},
+    EODHDClient, EODHDError,
 };
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
@@ -9,62 +12,50 @@ use serde::{Deserialize, Serialize};
 pub struct EODHDSentiment {
     #[serde(with = "eodhd_serde_date")]
     pub date: NaiveDate,
+    #[serde(with = "eodhd_string_int")]
     pub count: u64,
+    #[serde(deserialize_with = "eodhd_string_float::deserialize")]
     pub normalized: f64,
 }
 
-pub async fn get_sentiment(ticker: &str, from: EODHDDate, to: EODHDDate) -> Vec<EODHDSentiment> {
-    let token = env_eodhd_token();
-    let url = format!(
-        "{base_url}/sentiments?api_token={token}&s={ticker}&from={from}&to={to}",
-        base_url = BASE_URL,
-        token = token,
-        ticker = ticker,
-        from = NaiveDate::from(from),
-        to = NaiveDate::from(to),
-    );
-    println!("{:?}", url);
-    let request = reqwest::get(url.to_owned()).await;
-    if request.is_err() {
-        log::error!(
-            "REQUEST TO EODHD FAILED \n{:?}\n with {:?}",
-            url,
-            request.err()
-        );
-        panic!();
+impl EODHDClient {
+    pub async fn get_sentiment(
+        &self,
+        ticker: &EODHDTicker,
+        from: EODHDDate,
+        to: EODHDDate,
+    ) -> Result<Vec<EODHDSentiment>, EODHDError> {
+        let query = [
+            ("s", ticker.to_string()),
+            ("from", NaiveDate::from(from).to_string()),
+            ("to", NaiveDate::from(to).to_string()),
+        ];
+        let (url, body) = self
+            .get_ticker_text(EODHDEndpoint::Sentiment, ticker, "sentiments", &query)
+            .await?;
+        let ticker = ticker.to_string();
+        let mut jsoned = serde_json::from_str::<serde_json::Value>(&body)
+            .map_err(|e| EODHDError::decode(&url, &body, e))?;
+        let sentiments = match jsoned.get_mut(&ticker) {
+            Some(sentiments) => sentiments.take(),
+            None => {
+                log::error!("TICKER NOT FOUND IN EODHD RESPONSE {:?}", ticker);
+                return Err(EODHDError::UnknownTicker { url, ticker });
+            }
+        };
+        serde_json::from_value::<Vec<EODHDSentiment>>(sentiments)
+            .map_err(|e| EODHDError::decode(&url, &body, e))
     }
-    let response = request.unwrap().text().await;
-    if response.is_err() {
-        log::error!(
-            "CONVERTING EODHD RESPONSE TO TEXT FAILED with {:?}",
-            response.err()
-        );
-        panic!();
-    }
-    let jsoned: Result<serde_json::Value, serde_json::error::Error> =
-        serde_json::from_str(response.unwrap().as_str());
-    if jsoned.is_err() {
-        log::error!(
-            "{:?} CONVERTING EODHD RESPONSE TO JSON FAILED with {:?}",
-            url,
-            jsoned.err()
-        );
-        panic!();
-    }
-    let v = jsoned.unwrap();
-    let array = v[ticker.to_owned()].to_string();
-    if array == "undefined" {
-        log::error!("TICKER NOT FOUND IN EODHD RESPONSE {:?}", array);
-        panic!();
-    }
-    let parse_sentiment: Result<Vec<EODHDSentiment>, serde_json::error::Error> =
-        serde_json::from_str(array.as_str());
-    if parse_sentiment.is_err() {
-        log::error!(
-            "CONVERTING EODHD RESPONSE TO JSON FAILED with {:?}",
-            parse_sentiment.err()
-        );
-        panic!();
-    }
-    parse_sentiment.unwrap()
+}
+
+/// Shorthand for [EODHDClient::get_sentiment]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_sentiment(
+    ticker: &str,
+    from: EODHDDate,
+    to: EODHDDate,
+) -> Result<Vec<EODHDSentiment>, EODHDError> {
+    EODHDClient::from_env()?
+        .get_sentiment(&parse_ticker(ticker)?, from, to)
+        .await
 }
@@ -0,0 +1,274 @@
+use std::sync::Arc;
+
+use log::warn;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    env_eodhd_token,
+    error::{classify_response, redact_url, EODHDError},
+    rate_limit::{EODHDEndpoint, EODHDRateBudget, EODHDRateLimit, RateLimiter},
+    realtime::BASE_URL_SOCKET,
+    retry::{parse_retry_after, EODHDRetryPolicy},
+    ticker::EODHDTicker,
+    BASE_URL,
+};
+
+/// Owns everything needed to talk to EODHD:
+/// the api token, the REST and socket urls
+/// and a [reqwest::Client] whose connection
+/// pool is shared by all requests (and clones)
+/// of this client.
+#[derive(Clone)]
+pub struct EODHDClient {
+    token: String,
+    base_url: String,
+    socket_url: String,
+    http: reqwest::Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    retry_policy: EODHDRetryPolicy,
+}
+
+/// The token is left out, so clients
+/// can be logged without leaking it
+impl std::fmt::Debug for EODHDClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EODHDClient")
+            .field("token", &"<redacted>")
+            .field("base_url", &self.base_url)
+            .field("socket_url", &self.socket_url)
+            .field("http", &self.http)
+            .field("rate_limiter", &self.rate_limiter)
+            .field("retry_policy", &self.retry_policy)
+            .finish()
+    }
+}
+
+impl EODHDClient {
+    /// Creates a client for the given api token
+    /// talking to the official EODHD endpoints.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            base_url: BASE_URL.to_string(),
+            socket_url: BASE_URL_SOCKET.to_string(),
+            http: reqwest::Client::new(),
+            rate_limiter: None,
+            retry_policy: EODHDRetryPolicy::none(),
+        }
+    }
+
+    /// Creates a client with the token found
+    /// in the environment variable `EODHD_TOKEN`.
+    pub fn from_env() -> Result<Self, EODHDError> {
+        Ok(Self::new(env_eodhd_token()?))
+    }
+
+    /// Replaces the REST base url, e.g. for pointing
+    /// the client at a local mock server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Replaces the websocket base url.
+    pub fn with_socket_url(mut self, socket_url: impl Into<String>) -> Self {
+        self.socket_url = socket_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Uses a preconfigured [reqwest::Client]
+    /// (timeouts, proxies etc.) for all requests.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Limits requests per minute and api calls per day
+    /// on the client side, clones of the client share
+    /// the same budget. Without it requests are unlimited.
+    pub fn with_rate_limit(mut self, limit: EODHDRateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// Retries transient failures of all REST
+    /// endpoints, by default requests aren't retried.
+    pub fn with_retry_policy(mut self, retry_policy: EODHDRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Remaining requests/api calls, `None`
+    /// if no rate limit is configured.
+    pub fn rate_budget(&self) -> Option<EODHDRateBudget> {
+        self.rate_limiter.as_ref().map(|limiter| limiter.budget())
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn socket_url(&self) -> &str {
+        &self.socket_url
+    }
+
+    pub(crate) fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Waits for the rate limiter (if any) and
+    /// sends a GET request to `{base_url}/{path}` with
+    /// the api token and `fmt=json` appended to `query`.
+    /// Returns the redacted url together with the body,
+    /// non success statuses and EODHD's plain text
+    /// error messages are turned into [EODHDError]s.
+    /// Failed requests are retried according to the [EODHDRetryPolicy].
+    pub(crate) async fn get_text(
+        &self,
+        endpoint: EODHDEndpoint,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<(String, String), EODHDError> {
+        self.request_text(endpoint, None, path, query).await
+    }
+
+    /// Same as [EODHDClient::get_text] for requests about
+    /// a single `ticker`, which is reported in
+    /// [EODHDError::UnknownTicker] if EODHD doesn't know it.
+    pub(crate) async fn get_ticker_text(
+        &self,
+        endpoint: EODHDEndpoint,
+        ticker: &EODHDTicker,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<(String, String), EODHDError> {
+        self.request_text(endpoint, Some(ticker), path, query).await
+    }
+
+    async fn request_text(
+        &self,
+        endpoint: EODHDEndpoint,
+        ticker: Option<&EODHDTicker>,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<(String, String), EODHDError> {
+        let mut attempt = 0;
+        loop {
+            match self.get_text_once(endpoint, ticker, path, query).await {
+                Err(e)
+                    if attempt + 1 < self.retry_policy.max_attempts
+                        && self.retry_policy.is_retryable(&e) =>
+                {
+                    let delay = self.retry_policy.delay(attempt, &e);
+                    warn!("retrying in {:?} after {}", delay, e);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn get_text_once(
+        &self,
+        endpoint: EODHDEndpoint,
+        ticker: Option<&EODHDTicker>,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<(String, String), EODHDError> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(endpoint).await?;
+        }
+        let url = format!("{}/{}", self.base_url, path.trim_start_matches('/'));
+        let request = self
+            .http
+            .get(url.as_str())
+            .query(query)
+            .query(&[("api_token", self.token.as_str()), ("fmt", "json")])
+            .build()
+            .map_err(|source| EODHDError::Transport {
+                url: url.clone(),
+                source: source.without_url(),
+            })?;
+        let url = redact_url(request.url());
+
+        let response = match self.http.execute(request).await {
+            Ok(response) => response,
+            Err(source) => {
+                // reqwest keeps the url (including the token) around
+                let source = source.without_url();
+                warn!("request to {} failed {:?}", url, source);
+                return Err(EODHDError::Transport { url, source });
+            }
+        };
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(source) => {
+                let source = source.without_url();
+                warn!("reading response of {} failed {:?}", url, source);
+                return Err(EODHDError::Transport { url, source });
+            }
+        };
+        match classify_response(&url, ticker, status, retry_after, &body) {
+            Some(e) => {
+                warn!("{}", e);
+                Err(e)
+            }
+            None => Ok((url, body)),
+        }
+    }
+
+    /// Same as [EODHDClient::get_text] but
+    /// decodes the body into `T`.
+    pub(crate) async fn get_json<T: DeserializeOwned>(
+        &self,
+        endpoint: EODHDEndpoint,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, EODHDError> {
+        let (url, body) = self.get_text(endpoint, path, query).await?;
+        decode_json(&url, &body)
+    }
+
+    /// Same as [EODHDClient::get_ticker_text] but
+    /// decodes the body into `T`.
+    pub(crate) async fn get_ticker_json<T: DeserializeOwned>(
+        &self,
+        endpoint: EODHDEndpoint,
+        ticker: &EODHDTicker,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, EODHDError> {
+        let (url, body) = self.get_ticker_text(endpoint, ticker, path, query).await?;
+        decode_json(&url, &body)
+    }
+}
+
+fn decode_json<T: DeserializeOwned>(url: &str, body: &str) -> Result<T, EODHDError> {
+    serde_json::from_str::<T>(body).map_err(|e| {
+        warn!("unable to decode response of {} {:?}", url, e);
+        EODHDError::decode(url, body, e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_hides_token() {
+        let client = EODHDClient::new("secret-token-123");
+        let debug = format!("{:?}", client);
+        assert!(!debug.contains("secret-token-123"));
+        assert!(debug.contains("<redacted>"));
+
+        let resolver = crate::search::EODHDIdResolver::new(client);
+        assert!(!format!("{:?}", resolver).contains("secret-token-123"));
+    }
+}
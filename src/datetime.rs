@@ -1,27 +1,64 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use std::fmt::Display;
 
 pub struct EODHDDate(pub i32, pub u32, pub u32);
 
 impl From<EODHDDate> for NaiveDate {
     fn from(ed: EODHDDate) -> Self {
-        NaiveDate::from_ymd(ed.0, ed.1, ed.2)
+        NaiveDate::from_ymd_opt(ed.0, ed.1, ed.2).expect("EODHDDate out of range")
     }
 }
 
+/// A unix timestamp in seconds (end of period,
+/// intraday and delayed data) as UTC time
+pub fn eodhd_timestamp(seconds: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(seconds, 0).single()
+}
+
+/// A unix timestamp in milliseconds (realtime
+/// ticks) as UTC time
+pub fn eodhd_timestamp_ms(millis: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt(millis).single()
+}
+
+/// Data carrying an EODHD timestamp, converted to
+/// any timezone, e.g. the exchange's from
+/// [crate::exchanges::EODHDExchangeDetails::tz]
+pub trait EODHDTimestamped {
+    /// `None` if EODHD didn't deliver a valid timestamp
+    fn time(&self) -> Option<DateTime<Utc>>;
+
+    fn time_in<Tz: TimeZone>(&self, tz: &Tz) -> Option<DateTime<Tz>> {
+        self.time().map(|time| time.with_timezone(tz))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EODHDInterval {
     Minute,
     Minute5,
     Hour,
 }
 
-impl ToString for EODHDInterval {
-    fn to_string(&self) -> String {
-        match self {
+impl Display for EODHDInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             EODHDInterval::Hour => "1h",
             EODHDInterval::Minute => "1m",
             EODHDInterval::Minute5 => "5m",
+        })
+    }
+}
+
+impl EODHDInterval {
+    /// Longest range (in seconds) EODHD
+    /// answers with a single intraday request
+    pub fn max_request_range(&self) -> i64 {
+        const DAY: i64 = 24 * 60 * 60;
+        match self {
+            EODHDInterval::Minute => 120 * DAY,
+            EODHDInterval::Minute5 | EODHDInterval::Hour => 600 * DAY,
         }
-        .to_string()
     }
 }
 
@@ -35,8 +72,8 @@ pub mod eodhd_serde_opt_date {
     where
         S: Serializer,
     {
-        if date.is_some() {
-            let s = format!("{}", date.unwrap().format(FORMAT));
+        if let Some(date) = date {
+            let s = format!("{}", date.format(FORMAT));
             return serializer.serialize_str(&s);
         }
         serializer.serialize_none()
@@ -46,12 +83,14 @@ pub mod eodhd_serde_opt_date {
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
+        // EODHD uses null, "" and "0000-00-00" for unknown dates
+        let s = match Option::<String>::deserialize(deserializer)? {
+            Some(s) if !s.is_empty() && s != "0000-00-00" => s,
+            _ => return Ok(None),
+        };
         let parsed = NaiveDate::parse_from_str(&s, FORMAT);
         match parsed {
-            Ok(parsed) => {
-                Ok(Some(parsed))
-            },
+            Ok(parsed) => Ok(Some(parsed)),
             Err(e) => {
                 error!("error parsing serde date {:?}", e);
                 Err(serde::de::Error::custom("faulty eodhd date"))
@@ -1,41 +1,100 @@
 use chrono::NaiveDate;
-use log::warn;
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
-use super::{env_eodhd_token, EODHDError};
-
-const BASE_URL: &str = "https://eodhistoricaldata.com/api/eod";
+use super::{
+    datetime::eodhd_serde_opt_date,
+    eodhd_opt_string_float, eodhd_string_float, eodhd_string_int,
+    rate_limit::EODHDEndpoint,
+    splits_dividends::EODHDSplitRatio,
+    ticker::{parse_ticker, EODHDTicker},
+    EODHDClient, EODHDError,
+};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct EODHDEndOfPeriod {
     pub date: NaiveDate,
-    #[serde(rename = "open")]
+    #[serde(rename = "open", deserialize_with = "eodhd_string_float::deserialize")]
     pub o: f64,
-    #[serde(rename = "high")]
+    #[serde(rename = "high", deserialize_with = "eodhd_string_float::deserialize")]
     pub h: f64,
-    #[serde(rename = "low")]
+    #[serde(rename = "low", deserialize_with = "eodhd_string_float::deserialize")]
     pub l: f64,
-    #[serde(rename = "close")]
+    #[serde(rename = "close", deserialize_with = "eodhd_string_float::deserialize")]
     pub unadjusted_close: f64,
-    #[serde(rename = "adjusted_close")]
+    #[serde(
+        rename = "adjusted_close",
+        deserialize_with = "eodhd_string_float::deserialize"
+    )]
     pub c: f64,
-    #[serde(rename = "volume")]
+    #[serde(rename = "volume", with = "eodhd_string_int")]
     pub v: i64,
 }
 
+impl EODHDEndOfPeriod {
+    /// `adjusted_close / close`, 1 if either is missing
+    pub fn adjustment_ratio(&self) -> f64 {
+        if !self.unadjusted_close.is_finite() || self.unadjusted_close == 0.0 || !self.c.is_finite()
+        {
+            return 1.0;
+        }
+        self.c / self.unadjusted_close
+    }
+
+    /// Scales open, high, low and close with the
+    /// [EODHDEndOfPeriod::adjustment_ratio] and divides the volume
+    /// by it, so the whole bar is comparable across splits.
+    pub fn adjusted(&self) -> EODHDAdjustedEndOfPeriod {
+        let ratio = self.adjustment_ratio();
+        let volume_factor = if ratio == 0.0 { 1.0 } else { 1.0 / ratio };
+        EODHDAdjustedEndOfPeriod::scaled(*self, ratio, volume_factor)
+    }
+}
+
+/// An [EODHDEndOfPeriod] with all prices and the
+/// volume adjusted for splits (and dividends)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EODHDAdjustedEndOfPeriod {
+    pub date: NaiveDate,
+    pub o: f64,
+    pub h: f64,
+    pub l: f64,
+    pub c: f64,
+    pub v: f64,
+    /// the unadjusted prices were multiplied with
+    pub factor: f64,
+    /// the bar as delivered by EODHD
+    pub raw: EODHDEndOfPeriod,
+}
+
+impl EODHDAdjustedEndOfPeriod {
+    pub(crate) fn scaled(raw: EODHDEndOfPeriod, price_factor: f64, volume_factor: f64) -> Self {
+        Self {
+            date: raw.date,
+            o: raw.o * price_factor,
+            h: raw.h * price_factor,
+            l: raw.l * price_factor,
+            c: raw.unadjusted_close * price_factor,
+            v: raw.v as f64 * volume_factor,
+            factor: price_factor,
+            raw,
+        }
+    }
+}
+
 pub enum EODHDPeriod {
     Daily,
     Weekly,
     Monthly,
 }
 
-impl ToString for EODHDPeriod {
-    fn to_string(&self) -> String {
-        match self {
-            EODHDPeriod::Daily => "d".to_string(),
-            EODHDPeriod::Weekly => "w".to_string(),
-            EODHDPeriod::Monthly => "m".to_string(),
-        }
+impl Display for EODHDPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EODHDPeriod::Daily => "d",
+            EODHDPeriod::Weekly => "w",
+            EODHDPeriod::Monthly => "m",
+        })
     }
 }
 
@@ -48,67 +107,248 @@ pub struct EODHDEndOfPeriodFilter {
     pub period: Option<EODHDPeriod>,
 }
 
+/// One bar of the bulk download for a whole exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDBulkEndOfPeriod {
+    /// ticker code without exchange suffix
+    pub code: String,
+    pub exchange_short_name: String,
+    #[serde(flatten)]
+    pub bar: EODHDEndOfPeriod,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDBulkSplit {
+    pub code: String,
+    pub exchange: String,
+    pub date: NaiveDate,
+    pub split: EODHDSplitRatio,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDBulkDividend {
+    pub code: String,
+    pub exchange: String,
+    /// ex dividend date
+    pub date: NaiveDate,
+    #[serde(deserialize_with = "eodhd_string_float::deserialize")]
+    pub dividend: f64,
+    pub currency: Option<String>,
+    #[serde(rename = "declarationDate", default, with = "eodhd_serde_opt_date")]
+    pub declaration_date: Option<NaiveDate>,
+    #[serde(rename = "recordDate", default, with = "eodhd_serde_opt_date")]
+    pub record_date: Option<NaiveDate>,
+    #[serde(rename = "paymentDate", default, with = "eodhd_serde_opt_date")]
+    pub payment_date: Option<NaiveDate>,
+    pub period: Option<String>,
+    #[serde(
+        rename = "unadjustedValue",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub unadjusted_value: Option<f64>,
+}
+
+#[derive(Default)]
+pub struct EODHDBulkEndOfPeriodFilter {
+    // The trading day, if not provided the last one will be used.
+    pub date: Option<chrono::NaiveDate>,
+    // Only these tickers (e.g. "AAPL.US"), each costs one api call
+    // instead of 100 for the whole exchange.
+    pub symbols: Option<Vec<EODHDTicker>>,
+}
+
+impl EODHDBulkEndOfPeriodFilter {
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![];
+        if let Some(date) = self.date {
+            query.push(("date", date.format("%Y-%m-%d").to_string()));
+        }
+        if let Some(symbols) = &self.symbols {
+            query.push((
+                "symbols",
+                symbols
+                    .iter()
+                    .map(EODHDTicker::to_string)
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ));
+        }
+        query
+    }
+
+    fn endpoint(&self) -> EODHDEndpoint {
+        EODHDEndpoint::BulkEndOfPeriod {
+            symbols: self.symbols.as_ref().map(|s| s.len() as u32).unwrap_or(0),
+        }
+    }
+}
+
+impl EODHDClient {
+    pub async fn get_end_of_period(
+        &self,
+        ticker: &EODHDTicker,
+        filter: Option<EODHDEndOfPeriodFilter>,
+    ) -> Result<Vec<EODHDEndOfPeriod>, EODHDError> {
+        let mut query = vec![];
+        if let Some(filter) = filter {
+            if let Some(from) = filter.from {
+                query.push(("from", from.format("%Y-%m-%d").to_string()));
+            }
+            if let Some(to) = filter.to {
+                query.push(("to", to.format("%Y-%m-%d").to_string()));
+            }
+            if let Some(period) = filter.period {
+                query.push(("period", period.to_string()));
+            }
+        }
+
+        self.get_ticker_json(
+            EODHDEndpoint::EndOfPeriod,
+            ticker,
+            &format!("eod/{}", ticker.path_segment()),
+            &query,
+        )
+        .await
+    }
+
+    /// Same as [EODHDClient::get_end_of_period] but with
+    /// open, high, low, close and volume adjusted
+    /// (see [EODHDEndOfPeriod::adjusted]), the values as
+    /// delivered are kept in `raw`.
+    pub async fn get_adjusted_end_of_period(
+        &self,
+        ticker: &EODHDTicker,
+        filter: Option<EODHDEndOfPeriodFilter>,
+    ) -> Result<Vec<EODHDAdjustedEndOfPeriod>, EODHDError> {
+        Ok(self
+            .get_end_of_period(ticker, filter)
+            .await?
+            .iter()
+            .map(EODHDEndOfPeriod::adjusted)
+            .collect())
+    }
+
+    /// End of day bars of all tickers on `exchange`
+    /// (e.g. "US") for a single day.
+    pub async fn get_bulk_end_of_period(
+        &self,
+        exchange: &str,
+        filter: Option<EODHDBulkEndOfPeriodFilter>,
+    ) -> Result<Vec<EODHDBulkEndOfPeriod>, EODHDError> {
+        let filter = filter.unwrap_or_default();
+        self.get_json(
+            filter.endpoint(),
+            &format!("eod-bulk-last-day/{}", exchange),
+            &filter.query(),
+        )
+        .await
+    }
+
+    /// Splits of all tickers on `exchange` for a single day.
+    pub async fn get_bulk_splits(
+        &self,
+        exchange: &str,
+        filter: Option<EODHDBulkEndOfPeriodFilter>,
+    ) -> Result<Vec<EODHDBulkSplit>, EODHDError> {
+        let filter = filter.unwrap_or_default();
+        let mut query = filter.query();
+        query.push(("type", "splits".to_string()));
+        self.get_json(
+            filter.endpoint(),
+            &format!("eod-bulk-last-day/{}", exchange),
+            &query,
+        )
+        .await
+    }
+
+    /// Dividends of all tickers on `exchange` for a single day.
+    pub async fn get_bulk_dividends(
+        &self,
+        exchange: &str,
+        filter: Option<EODHDBulkEndOfPeriodFilter>,
+    ) -> Result<Vec<EODHDBulkDividend>, EODHDError> {
+        let filter = filter.unwrap_or_default();
+        let mut query = filter.query();
+        query.push(("type", "dividends".to_string()));
+        self.get_json(
+            filter.endpoint(),
+            &format!("eod-bulk-last-day/{}", exchange),
+            &query,
+        )
+        .await
+    }
+}
+
+/// Shorthand for [EODHDClient::get_end_of_period]
+/// with a client created from `EODHD_TOKEN`.
 pub async fn get_end_of_period(
     ticker: &str,
     filter: Option<EODHDEndOfPeriodFilter>,
 ) -> Result<Vec<EODHDEndOfPeriod>, EODHDError> {
-    let mut url = format!(
-        "{base_url}/{ticker}?api_token={token}&fmt=json",
-        base_url = BASE_URL,
-        ticker = ticker,
-        token = env_eodhd_token()
-    );
-    if filter.is_some() {
-        let unwraped_filter = filter.unwrap();
-        if unwraped_filter.from.is_some() {
-            url = format!(
-                "{url}&from={from}",
-                url = url,
-                from = unwraped_filter.from.unwrap().format("%Y-%m-%d")
-            );
-        }
-        if unwraped_filter.to.is_some() {
-            url = format!(
-                "{url}&to={to}",
-                url = url,
-                to = unwraped_filter.to.unwrap().format("%Y-%m-%d")
-            );
-        }
-        if unwraped_filter.to.is_some() {
-            url = format!(
-                "{url}&to={to}",
-                url = url,
-                to = unwraped_filter.to.unwrap().format("%Y-%m-%d")
-            );
-        }
-        if unwraped_filter.period.is_some() {
-            url = format!(
-                "{url}&period={period}",
-                url = url,
-                period = unwraped_filter.period.unwrap().to_string()
-            );
+    EODHDClient::from_env()?
+        .get_end_of_period(&parse_ticker(ticker)?, filter)
+        .await
+}
+
+/// Shorthand for [EODHDClient::get_adjusted_end_of_period]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_adjusted_end_of_period(
+    ticker: &str,
+    filter: Option<EODHDEndOfPeriodFilter>,
+) -> Result<Vec<EODHDAdjustedEndOfPeriod>, EODHDError> {
+    EODHDClient::from_env()?
+        .get_adjusted_end_of_period(&parse_ticker(ticker)?, filter)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(unadjusted_close: f64, c: f64) -> EODHDEndOfPeriod {
+        EODHDEndOfPeriod {
+            date: NaiveDate::from_ymd_opt(2023, 1, 2).unwrap(),
+            o: 10.0,
+            h: 12.0,
+            l: 8.0,
+            unadjusted_close,
+            c,
+            v: 100,
         }
     }
 
-    let request = reqwest::get(url).await;
+    #[test]
+    fn adjusted_scales_the_whole_bar() {
+        let adjusted = bar(10.0, 5.0).adjusted();
+        assert_eq!(adjusted.factor, 0.5);
+        assert_eq!((adjusted.o, adjusted.h, adjusted.l), (5.0, 6.0, 4.0));
+        assert_eq!(adjusted.c, 5.0);
+        assert_eq!(adjusted.v, 200.0);
+    }
 
-    if request.is_err() {
-        let description: &str = "request failed";
-        warn!("{}", description);
-        return Err(EODHDError {
-            description: description.to_string(),
-            inner_error: Box::new(request.err().unwrap()),
-        });
+    #[test]
+    fn missing_close_keeps_the_bar_unadjusted() {
+        for raw in [bar(f64::NAN, 5.0), bar(0.0, 5.0), bar(10.0, f64::NAN)] {
+            assert_eq!(raw.adjustment_ratio(), 1.0);
+            let adjusted = raw.adjusted();
+            assert_eq!((adjusted.o, adjusted.h, adjusted.l), (10.0, 12.0, 8.0));
+            assert_eq!(adjusted.v, 100.0);
+        }
     }
 
-    let end_of_day = request.unwrap().json::<Vec<EODHDEndOfPeriod>>().await;
-    if end_of_day.is_err() {
-        let description: &str = "parsing end of day failed";
-        warn!("{}", description);
-        return Err(EODHDError {
-            description: description.to_string(),
-            inner_error: Box::new(end_of_day.err().unwrap()),
-        });
+    #[test]
+    fn serializes_prices_as_numbers() {
+        let json = r#"{"date":"2023-01-02","open":"10.5","high":12,"low":8,"close":10,"adjusted_close":"NA","volume":"100"}"#;
+        let parsed: EODHDEndOfPeriod = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.o, 10.5);
+        assert!(parsed.c.is_nan());
+
+        let value = serde_json::to_value(bar(10.0, 5.0)).unwrap();
+        assert_eq!(value["open"], serde_json::json!(10.0));
+        assert_eq!(value["adjusted_close"], serde_json::json!(5.0));
+        assert_eq!(value["volume"], serde_json::json!(100));
+        let again: EODHDEndOfPeriod = serde_json::from_value(value).unwrap();
+        assert_eq!((again.o, again.c, again.v), (10.0, 5.0, 100));
     }
-    Ok(end_of_day.unwrap())
 }
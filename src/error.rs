@@ -0,0 +1,356 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{rate_limit::EODHDBudgetWindow, ticker::EODHDTicker};
+
+/// Maximum amount of characters of a response body
+/// kept in [EODHDError::Decode] for debugging.
+const SNIPPET_LENGTH: usize = 256;
+
+/// Every public function of this crate
+/// reports failures with this type.
+#[derive(Debug)]
+pub enum EODHDError {
+    /// The environment variable `EODHD_TOKEN` isn't set
+    MissingToken,
+    /// The request didn't produce a response
+    /// (dns, tls, timeouts, connection resets...)
+    Transport {
+        /// url with the api token redacted
+        url: String,
+        source: reqwest::Error,
+    },
+    /// EODHD answered with a non success status code
+    Status {
+        /// url with the api token redacted
+        url: String,
+        status: u16,
+        body: String,
+    },
+    /// The response couldn't be decoded into the expected type
+    Decode {
+        /// url with the api token redacted
+        url: String,
+        /// beginning of the offending payload
+        snippet: String,
+        source: serde_json::Error,
+    },
+    /// Too many requests per minute, worth retrying later
+    RateLimited {
+        /// url with the api token redacted
+        url: String,
+        body: String,
+        /// value of the `Retry-After` header
+        retry_after: Option<std::time::Duration>,
+    },
+    /// The daily api calls of the plan are used up, requests
+    /// fail until the quota resets (so they aren't retried)
+    DailyLimitReached {
+        /// url with the api token redacted
+        url: String,
+        body: String,
+    },
+    /// The api token is invalid (401, 403)
+    Unauthorized {
+        /// url with the api token redacted
+        url: String,
+        body: String,
+    },
+    /// The endpoint isn't part of the subscribed plan (402)
+    PlanRequired {
+        /// url with the api token redacted
+        url: String,
+        body: String,
+    },
+    /// The client side rate limiter refused the request
+    /// before it was sent, to protect the quota
+    BudgetExhausted {
+        window: EODHDBudgetWindow,
+        /// api calls the request would have cost
+        cost: u32,
+        /// api calls left in `window`
+        remaining: u32,
+    },
+    /// EODHD doesn't know the requested ticker
+    UnknownTicker {
+        /// url with the api token redacted
+        url: String,
+        ticker: String,
+    },
+    /// The timezone EODHD reports for an exchange
+    /// isn't in the tz database
+    UnknownTimezone { exchange: String, timezone: String },
+    /// A ticker string couldn't be parsed into an
+    /// [crate::ticker::EODHDTicker], no request was sent
+    InvalidTicker { ticker: String, reason: String },
+    /// Connecting to or reading from the realtime websocket failed
+    Socket(Box<tokio_tungstenite::tungstenite::Error>),
+    /// The background task owning the other end of a channel is gone
+    ChannelClosed,
+}
+
+impl EODHDError {
+    pub(crate) fn decode(url: &str, payload: &str, source: serde_json::Error) -> Self {
+        EODHDError::Decode {
+            url: url.to_string(),
+            snippet: payload.chars().take(SNIPPET_LENGTH).collect(),
+            source,
+        }
+    }
+}
+
+/// EODHD answers errors with plain text bodies
+/// (sometimes even with a 200 status), e.g.
+/// "Ticker Not Found." or "You exceeded your daily API requests limit".
+/// Returns `None` when the response looks like a proper json answer.
+/// * `ticker` - the ticker the request is about, not found
+///   answers are reported as [EODHDError::UnknownTicker] only then
+pub(crate) fn classify_response(
+    url: &str,
+    ticker: Option<&EODHDTicker>,
+    status: u16,
+    retry_after: Option<std::time::Duration>,
+    body: &str,
+) -> Option<EODHDError> {
+    let trimmed = body.trim_start();
+    let is_json = trimmed.starts_with('{') || trimmed.starts_with('[');
+    if (200..300).contains(&status) && (is_json || trimmed.is_empty()) {
+        return None;
+    }
+
+    let url = url.to_string();
+    let message = body.trim().to_string();
+    let lowered = message.to_lowercase();
+    // comes with 402, 403 or 429, check it before those
+    if lowered.contains("daily") && (lowered.contains("exceeded") || lowered.contains("limit")) {
+        return Some(EODHDError::DailyLimitReached { url, body: message });
+    }
+    if status == 429 || lowered.contains("exceeded") || lowered.contains("limit reached") {
+        return Some(EODHDError::RateLimited {
+            url,
+            body: message,
+            retry_after,
+        });
+    }
+    if status == 401 || status == 403 || lowered.contains("unauthenticated") {
+        return Some(EODHDError::Unauthorized { url, body: message });
+    }
+    if status == 402 {
+        return Some(EODHDError::PlanRequired { url, body: message });
+    }
+    if let Some(ticker) = ticker {
+        if status == 404 || lowered.contains("not found") {
+            return Some(EODHDError::UnknownTicker {
+                url,
+                ticker: ticker.to_string(),
+            });
+        }
+    }
+    if (200..300).contains(&status) {
+        // plain text we don't know about, let decoding report it
+        return None;
+    }
+    Some(EODHDError::Status {
+        url,
+        status,
+        body: message,
+    })
+}
+
+impl Display for EODHDError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EODHDError::MissingToken => {
+                f.write_str("Please add EODHD_TOKEN for authentication to env")
+            }
+            EODHDError::Transport { url, source } => {
+                write!(f, "request to {} failed: {}", url, source)
+            }
+            EODHDError::Status { url, status, body } => {
+                write!(f, "{} responded with status {}: {}", url, status, body)
+            }
+            EODHDError::Decode {
+                url,
+                snippet,
+                source,
+            } => write!(
+                f,
+                "unable to decode response of {}: {} (payload starts with {:?})",
+                url, source, snippet
+            ),
+            EODHDError::RateLimited { url, body, .. } => {
+                write!(f, "rate limit exceeded requesting {}: {}", url, body)
+            }
+            EODHDError::DailyLimitReached { url, body } => {
+                write!(f, "daily api calls used up requesting {}: {}", url, body)
+            }
+            EODHDError::Unauthorized { url, body } => {
+                write!(f, "unauthorized requesting {}: {}", url, body)
+            }
+            EODHDError::PlanRequired { url, body } => {
+                write!(f, "plan doesn't include {}: {}", url, body)
+            }
+            EODHDError::BudgetExhausted {
+                window,
+                cost,
+                remaining,
+            } => write!(
+                f,
+                "request costs {} api calls but only {} are left this {}",
+                cost, remaining, window
+            ),
+            EODHDError::UnknownTicker { url, ticker } => {
+                write!(f, "ticker {} not found requesting {}", ticker, url)
+            }
+            EODHDError::UnknownTimezone { exchange, timezone } => {
+                write!(
+                    f,
+                    "unknown timezone {:?} of exchange {}",
+                    timezone, exchange
+                )
+            }
+            EODHDError::InvalidTicker { reason, .. } => f.write_str(reason),
+            EODHDError::Socket(e) => write!(f, "realtime socket failed: {}", e),
+            EODHDError::ChannelClosed => f.write_str("channel to background task is closed"),
+        }
+    }
+}
+
+impl Error for EODHDError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EODHDError::Transport { source, .. } => Some(source),
+            EODHDError::Decode { source, .. } => Some(source),
+            EODHDError::Socket(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for EODHDError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        EODHDError::Socket(Box::new(e))
+    }
+}
+
+impl<T> From<tokio::sync::mpsc::error::SendError<T>> for EODHDError {
+    fn from(_: tokio::sync::mpsc::error::SendError<T>) -> Self {
+        EODHDError::ChannelClosed
+    }
+}
+
+/// Replaces the value of the `api_token` query
+/// parameter, so urls can be logged safely.
+pub(crate) fn redact_url(url: &url::Url) -> String {
+    let mut redacted = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| {
+            if k == "api_token" {
+                (k.to_string(), "REDACTED".to_string())
+            } else {
+                (k.to_string(), v.to_string())
+            }
+        })
+        .collect();
+    if pairs.is_empty() {
+        redacted.set_query(None);
+    } else {
+        redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    redacted.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://eodhd.com/api/eod/AAPL.US?api_token=REDACTED";
+
+    fn classify(ticker: Option<&str>, status: u16, body: &str) -> Option<EODHDError> {
+        let ticker = ticker.map(|ticker| ticker.parse::<EODHDTicker>().unwrap());
+        classify_response(URL, ticker.as_ref(), status, None, body)
+    }
+
+    #[test]
+    fn redacts_the_token() {
+        let url = url::Url::parse(
+            "https://eodhd.com/api/eod/AAPL.US?from=2023-01-01&api_token=secret&fmt=json",
+        )
+        .unwrap();
+        let redacted = redact_url(&url);
+        assert!(!redacted.contains("secret"));
+        assert!(redacted.contains("api_token=REDACTED"));
+        assert!(redacted.contains("from=2023-01-01"));
+        let url = url::Url::parse("https://eodhd.com/api/exchanges-list/").unwrap();
+        assert_eq!(redact_url(&url), "https://eodhd.com/api/exchanges-list/");
+    }
+
+    #[test]
+    fn conversions_and_sources() {
+        let (tx, rx) = tokio::sync::mpsc::channel::<u8>(1);
+        drop(rx);
+        let e: EODHDError = tx.blocking_send(1).unwrap_err().into();
+        assert!(matches!(e, EODHDError::ChannelClosed));
+        let source = serde_json::from_str::<u8>("x").unwrap_err();
+        let e = EODHDError::decode(URL, &"x".repeat(1000), source);
+        match &e {
+            EODHDError::Decode { snippet, .. } => assert_eq!(snippet.len(), SNIPPET_LENGTH),
+            e => panic!("unexpected {:?}", e),
+        }
+        assert!(e.source().is_some());
+        assert!(EODHDError::MissingToken.source().is_none());
+    }
+
+    #[test]
+    fn json_answers_pass() {
+        assert!(classify(None, 200, "[]").is_none());
+        assert!(classify(None, 200, " {\"a\": 1}").is_none());
+        assert!(classify(None, 200, "").is_none());
+    }
+
+    #[test]
+    fn not_found_is_an_unknown_ticker_only_for_ticker_requests() {
+        match classify(Some("FOO.US"), 404, "Ticker Not Found.") {
+            Some(EODHDError::UnknownTicker { ticker, .. }) => assert_eq!(ticker, "FOO.US"),
+            e => panic!("unexpected {:?}", e),
+        }
+        match classify(Some("FOO.US"), 200, "Ticker Not Found.") {
+            Some(EODHDError::UnknownTicker { .. }) => {}
+            e => panic!("unexpected {:?}", e),
+        }
+        match classify(None, 404, "Not found") {
+            Some(EODHDError::Status { status: 404, .. }) => {}
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[test]
+    fn daily_limit_is_not_rate_limited() {
+        for status in [402, 403, 429] {
+            match classify(None, status, "You exceeded your daily API requests limit") {
+                Some(EODHDError::DailyLimitReached { .. }) => {}
+                e => panic!("unexpected {:?}", e),
+            }
+        }
+        match classify(None, 429, "Too Many Requests") {
+            Some(EODHDError::RateLimited { .. }) => {}
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[test]
+    fn status_codes() {
+        assert!(matches!(
+            classify(None, 401, "Unauthenticated"),
+            Some(EODHDError::Unauthorized { .. })
+        ));
+        assert!(matches!(
+            classify(None, 402, "Only for paid plans"),
+            Some(EODHDError::PlanRequired { .. })
+        ));
+        assert!(matches!(
+            classify(None, 503, "Service Unavailable"),
+            Some(EODHDError::Status { status: 503, .. })
+        ));
+    }
+}
@@ -0,0 +1,558 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::{fundamentals::eodhd_indexed_vec, rate_limit::EODHDEndpoint, EODHDClient, EODHDError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDExchange {
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// EODHD's code used as ticker suffix, e.g. "US" or "XETRA"
+    #[serde(rename = "Code")]
+    pub code: String,
+    /// can be a list, e.g. "XNAS, XNYS"
+    #[serde(rename = "OperatingMIC")]
+    pub operating_mic: Option<String>,
+    #[serde(rename = "Country")]
+    pub country: Option<String>,
+    #[serde(rename = "Currency")]
+    pub currency: Option<String>,
+    #[serde(rename = "CountryISO2")]
+    pub country_iso2: Option<String>,
+    #[serde(rename = "CountryISO3")]
+    pub country_iso3: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDExchangeDetails {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Code")]
+    pub code: String,
+    #[serde(rename = "OperatingMIC")]
+    pub operating_mic: Option<String>,
+    #[serde(rename = "Country")]
+    pub country: Option<String>,
+    #[serde(rename = "Currency")]
+    pub currency: Option<String>,
+    /// IANA name, e.g. "America/New_York"
+    #[serde(rename = "Timezone")]
+    pub timezone: String,
+    /// at the time of the request
+    #[serde(rename = "isOpen", default)]
+    pub is_open: bool,
+    #[serde(rename = "TradingHours")]
+    pub trading_hours: EODHDTradingHours,
+    #[serde(rename = "ExchangeHolidays", default, with = "eodhd_indexed_vec")]
+    pub holidays: Vec<EODHDExchangeHoliday>,
+    #[serde(rename = "ExchangeEarlyCloseDays", default, with = "eodhd_indexed_vec")]
+    pub early_close_days: Vec<EODHDExchangeHoliday>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDTradingHours {
+    /// exchange local time
+    #[serde(rename = "Open")]
+    pub open: NaiveTime,
+    /// exchange local time
+    #[serde(rename = "Close")]
+    pub close: NaiveTime,
+    #[serde(rename = "OpenUTC")]
+    pub open_utc: Option<NaiveTime>,
+    #[serde(rename = "CloseUTC")]
+    pub close_utc: Option<NaiveTime>,
+    #[serde(rename = "WorkingDays", with = "eodhd_weekdays")]
+    pub working_days: Vec<Weekday>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDExchangeHoliday {
+    #[serde(rename = "Holiday")]
+    pub holiday: String,
+    #[serde(rename = "Date")]
+    pub date: NaiveDate,
+    /// "official" (exchange closed) or "bank"
+    #[serde(rename = "Type")]
+    pub type_holiday: String,
+}
+
+impl EODHDExchangeDetails {
+    /// The exchange's timezone, `None` if
+    /// EODHD's name is unknown to chrono-tz
+    pub fn tz(&self) -> Option<Tz> {
+        self.timezone.parse().ok()
+    }
+}
+
+impl EODHDExchangeHoliday {
+    pub fn is_official(&self) -> bool {
+        self.type_holiday.eq_ignore_ascii_case("official")
+    }
+}
+
+pub struct EODHDExchangeDetailsFilter {
+    // The start date for holidays, if not provided 6 months ago.
+    pub from: Option<chrono::NaiveDate>,
+    // The end date for holidays, if not provided in 6 months.
+    pub to: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDSymbol {
+    /// without the exchange suffix
+    #[serde(rename = "Code")]
+    pub code: String,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "Country")]
+    pub country: Option<String>,
+    #[serde(rename = "Exchange")]
+    pub exchange: Option<String>,
+    #[serde(rename = "Currency")]
+    pub currency: Option<String>,
+    /// e.g. "Common Stock", "ETF", "FUND"
+    #[serde(rename = "Type")]
+    pub type_symbol: Option<String>,
+    #[serde(rename = "Isin")]
+    pub isin: Option<String>,
+    /// not part of the response, set by
+    /// [EODHDClient::get_exchange_symbols]
+    #[serde(skip_deserializing, default)]
+    pub delisted: bool,
+}
+
+/// Instrument types the symbol list can be filtered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDSymbolType {
+    CommonStock,
+    PreferredStock,
+    /// common and preferred stocks
+    Stock,
+    ETF,
+    Fund,
+}
+
+impl Display for EODHDSymbolType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EODHDSymbolType::CommonStock => "common_stock",
+            EODHDSymbolType::PreferredStock => "preferred_stock",
+            EODHDSymbolType::Stock => "stock",
+            EODHDSymbolType::ETF => "etf",
+            EODHDSymbolType::Fund => "fund",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EODHDSymbolListFilter {
+    // Only delisted symbols instead of only active ones.
+    pub delisted: bool,
+    // All types if not provided.
+    pub type_symbol: Option<EODHDSymbolType>,
+}
+
+/// Answers when an exchange trades, based on its
+/// [EODHDExchangeDetails]. Naive dates and times are
+/// exchange local, official holidays close the exchange
+/// for the whole day, early close days at their own close.
+/// Only holidays within the range requested for the
+/// details are known.
+#[derive(Debug, Clone)]
+pub struct EODHDMarketCalendar {
+    /// needed to answer for UTC times, see [EODHDExchangeDetails::tz]
+    pub timezone: Option<Tz>,
+    pub open: NaiveTime,
+    pub close: NaiveTime,
+    pub working_days: Vec<Weekday>,
+    pub holidays: HashSet<NaiveDate>,
+    /// close of the early close days
+    pub early_closes: HashMap<NaiveDate, NaiveTime>,
+}
+
+/// EODHD lists early close days without the time,
+/// this is the one of the US exchanges
+fn default_early_close() -> NaiveTime {
+    NaiveTime::from_hms_opt(13, 0, 0).expect("valid time")
+}
+
+impl From<&EODHDExchangeDetails> for EODHDMarketCalendar {
+    fn from(details: &EODHDExchangeDetails) -> Self {
+        Self {
+            timezone: details.tz(),
+            open: details.trading_hours.open,
+            close: details.trading_hours.close,
+            working_days: details.trading_hours.working_days.clone(),
+            holidays: details
+                .holidays
+                .iter()
+                .filter(|h| h.is_official())
+                .map(|h| h.date)
+                .collect(),
+            early_closes: details
+                .early_close_days
+                .iter()
+                .map(|day| {
+                    (
+                        day.date,
+                        default_early_close().min(details.trading_hours.close),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl EODHDMarketCalendar {
+    /// Sets the close of all early close days, as EODHD
+    /// doesn't deliver it (13:00 by default, like the US
+    /// exchanges)
+    pub fn with_early_close(mut self, close: NaiveTime) -> Self {
+        for early_close in self.early_closes.values_mut() {
+            *early_close = close;
+        }
+        self
+    }
+
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        self.working_days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// The close on `date`, earlier on early close days
+    pub fn close_on(&self, date: NaiveDate) -> NaiveTime {
+        self.early_closes.get(&date).copied().unwrap_or(self.close)
+    }
+
+    /// Whether the exchange is in its regular session
+    /// at the exchange local `at` (open inclusive, close exclusive)
+    pub fn is_open(&self, at: NaiveDateTime) -> bool {
+        self.is_trading_day(at.date())
+            && at.time() >= self.open
+            && at.time() < self.close_on(at.date())
+    }
+
+    /// Whether the exchange is in its regular session at `at`,
+    /// `None` without a known timezone
+    pub fn is_open_at(&self, at: DateTime<Utc>) -> Option<bool> {
+        let tz = self.timezone?;
+        Some(self.is_open(at.with_timezone(&tz).naive_local()))
+    }
+
+    /// The exchange local date at `at`, the date of the
+    /// daily bar a UTC time belongs to
+    pub fn trading_date(&self, at: DateTime<Utc>) -> Option<NaiveDate> {
+        let tz = self.timezone?;
+        Some(at.with_timezone(&tz).date_naive())
+    }
+
+    /// Open and close of the session on `date` in the exchange's
+    /// timezone, `None` if it isn't a trading day (or the
+    /// timezone is unknown)
+    pub fn session(&self, date: NaiveDate) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+        let tz = self.timezone?;
+        if !self.is_trading_day(date) {
+            return None;
+        }
+        let local = |time: NaiveTime| match tz.from_local_datetime(&date.and_time(time)) {
+            LocalResult::Single(t) => Some(t),
+            LocalResult::Ambiguous(earliest, _) => Some(earliest),
+            LocalResult::None => None,
+        };
+        Some((local(self.open)?, local(self.close_on(date))?))
+    }
+
+    /// The first trading day after `date`, `None` if
+    /// there is none within a year (e.g. no working days)
+    pub fn next_trading_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (1..=366)
+            .map(|days| date + Duration::days(days))
+            .find(|d| self.is_trading_day(*d))
+    }
+
+    /// The last trading day before `date`, `None` if
+    /// there is none within a year (e.g. no working days)
+    pub fn previous_trading_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (1..=366)
+            .map(|days| date - Duration::days(days))
+            .find(|d| self.is_trading_day(*d))
+    }
+
+    /// The next time the session opens at or after `at`
+    pub fn next_open(&self, at: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.is_trading_day(at.date()) && at.time() <= self.open {
+            return Some(at.date().and_time(self.open));
+        }
+        self.next_trading_day(at.date())
+            .map(|date| date.and_time(self.open))
+    }
+}
+
+impl EODHDClient {
+    /// All exchanges supported by EODHD
+    pub async fn get_exchanges(&self) -> Result<Vec<EODHDExchange>, EODHDError> {
+        self.get_json(EODHDEndpoint::Exchanges, "exchanges-list/", &[])
+            .await
+    }
+
+    /// Timezone, trading hours and holidays of exchange `code`
+    pub async fn get_exchange_details(
+        &self,
+        code: &str,
+        filter: Option<EODHDExchangeDetailsFilter>,
+    ) -> Result<EODHDExchangeDetails, EODHDError> {
+        let mut query = vec![];
+        if let Some(filter) = filter {
+            if let Some(from) = filter.from {
+                query.push(("from", from.format("%Y-%m-%d").to_string()));
+            }
+            if let Some(to) = filter.to {
+                query.push(("to", to.format("%Y-%m-%d").to_string()));
+            }
+        }
+        self.get_json(
+            EODHDEndpoint::ExchangeDetails,
+            &format!("exchange-details/{}", code),
+            &query,
+        )
+        .await
+    }
+
+    /// Symbols traded on `exchange`, EODHD returns either
+    /// active or delisted ones depending on `filter.delisted`
+    pub async fn get_exchange_symbols(
+        &self,
+        exchange: &str,
+        filter: Option<EODHDSymbolListFilter>,
+    ) -> Result<Vec<EODHDSymbol>, EODHDError> {
+        let filter = filter.unwrap_or_default();
+        let mut query = vec![];
+        if filter.delisted {
+            query.push(("delisted", "1".to_string()));
+        }
+        if let Some(type_symbol) = filter.type_symbol {
+            query.push(("type", type_symbol.to_string()));
+        }
+        let mut symbols: Vec<EODHDSymbol> = self
+            .get_json(
+                EODHDEndpoint::SymbolList,
+                &format!("exchange-symbol-list/{}", exchange),
+                &query,
+            )
+            .await?;
+        for symbol in symbols.iter_mut() {
+            symbol.delisted = filter.delisted;
+        }
+        Ok(symbols)
+    }
+
+    /// Active and delisted symbols of `exchange` (two requests),
+    /// to build universes free of survivorship bias
+    pub async fn get_exchange_universe(
+        &self,
+        exchange: &str,
+        type_symbol: Option<EODHDSymbolType>,
+    ) -> Result<Vec<EODHDSymbol>, EODHDError> {
+        let mut symbols = self
+            .get_exchange_symbols(
+                exchange,
+                Some(EODHDSymbolListFilter {
+                    delisted: false,
+                    type_symbol,
+                }),
+            )
+            .await?;
+        symbols.extend(
+            self.get_exchange_symbols(
+                exchange,
+                Some(EODHDSymbolListFilter {
+                    delisted: true,
+                    type_symbol,
+                }),
+            )
+            .await?,
+        );
+        Ok(symbols)
+    }
+
+    /// The timezone of exchange `code` from its details
+    pub async fn get_exchange_timezone(&self, code: &str) -> Result<Tz, EODHDError> {
+        let details = self.get_exchange_details(code, None).await?;
+        details.tz().ok_or(EODHDError::UnknownTimezone {
+            exchange: details.code,
+            timezone: details.timezone,
+        })
+    }
+
+    /// Shorthand for building an [EODHDMarketCalendar]
+    /// from [EODHDClient::get_exchange_details]
+    pub async fn get_market_calendar(
+        &self,
+        code: &str,
+        filter: Option<EODHDExchangeDetailsFilter>,
+    ) -> Result<EODHDMarketCalendar, EODHDError> {
+        let details = self.get_exchange_details(code, filter).await?;
+        Ok(EODHDMarketCalendar::from(&details))
+    }
+}
+
+/// Shorthand for [EODHDClient::get_exchanges]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_exchanges() -> Result<Vec<EODHDExchange>, EODHDError> {
+    EODHDClient::from_env()?.get_exchanges().await
+}
+
+/// Shorthand for [EODHDClient::get_exchange_details]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_exchange_details(
+    code: &str,
+    filter: Option<EODHDExchangeDetailsFilter>,
+) -> Result<EODHDExchangeDetails, EODHDError> {
+    EODHDClient::from_env()?
+        .get_exchange_details(code, filter)
+        .await
+}
+
+/// Shorthand for [EODHDClient::get_exchange_symbols]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_exchange_symbols(
+    exchange: &str,
+    filter: Option<EODHDSymbolListFilter>,
+) -> Result<Vec<EODHDSymbol>, EODHDError> {
+    EODHDClient::from_env()?
+        .get_exchange_symbols(exchange, filter)
+        .await
+}
+
+/// Working days are a comma separated
+/// list like "Mon,Tue,Wed,Thu,Fri"
+pub mod eodhd_weekdays {
+    use chrono::Weekday;
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(days: &[Weekday], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let s = days
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        serializer.serialize_str(&s)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Weekday>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+        s.split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(|d| {
+                d.parse::<Weekday>()
+                    .map_err(|_| serde::de::Error::custom(format!("faulty eodhd weekday {:?}", d)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn holiday(date: NaiveDate, type_holiday: &str) -> EODHDExchangeHoliday {
+        EODHDExchangeHoliday {
+            holiday: String::new(),
+            date,
+            type_holiday: type_holiday.to_string(),
+        }
+    }
+
+    fn nyse() -> EODHDMarketCalendar {
+        let details = EODHDExchangeDetails {
+            name: "USA Stocks".to_string(),
+            code: "US".to_string(),
+            operating_mic: None,
+            country: None,
+            currency: None,
+            timezone: "America/New_York".to_string(),
+            is_open: false,
+            trading_hours: EODHDTradingHours {
+                open: time(9, 30),
+                close: time(16, 0),
+                open_utc: None,
+                close_utc: None,
+                working_days: vec![
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                ],
+            },
+            holidays: vec![
+                holiday(date(7, 4), "official"),
+                holiday(date(10, 14), "bank"),
+            ],
+            early_close_days: vec![holiday(date(7, 3), "official")],
+        };
+        EODHDMarketCalendar::from(&details)
+    }
+
+    #[test]
+    fn holidays_are_closed() {
+        let calendar = nyse();
+        assert!(!calendar.is_trading_day(date(7, 4)));
+        assert!(!calendar.is_open(date(7, 4).and_time(time(12, 0))));
+        assert!(calendar.session(date(7, 4)).is_none());
+        // bank holidays don't close the exchange
+        assert!(calendar.is_open(date(10, 14).and_time(time(12, 0))));
+        assert_eq!(calendar.next_trading_day(date(7, 3)), Some(date(7, 5)));
+    }
+
+    #[test]
+    fn weekends_are_closed() {
+        let calendar = nyse();
+        assert!(!calendar.is_trading_day(date(7, 6)));
+        assert!(!calendar.is_open(date(7, 6).and_time(time(12, 0))));
+        assert_eq!(calendar.previous_trading_day(date(7, 8)), Some(date(7, 5)));
+        assert_eq!(
+            calendar.next_open(date(7, 5).and_time(time(17, 0))),
+            Some(date(7, 8).and_time(time(9, 30)))
+        );
+    }
+
+    #[test]
+    fn early_close_days_close_early() {
+        let calendar = nyse();
+        assert!(calendar.is_open(date(7, 3).and_time(time(12, 59))));
+        assert!(!calendar.is_open(date(7, 3).and_time(time(13, 0))));
+        let (open, close) = calendar.session(date(7, 3)).unwrap();
+        assert_eq!(open.naive_local(), date(7, 3).and_time(time(9, 30)));
+        assert_eq!(close.naive_local(), date(7, 3).and_time(time(13, 0)));
+        // 13:00 EDT
+        assert_eq!(close.with_timezone(&Utc).time(), time(17, 0));
+
+        let calendar = calendar.with_early_close(time(14, 0));
+        assert!(calendar.is_open(date(7, 3).and_time(time(13, 30))));
+        assert_eq!(calendar.close_on(date(7, 2)), time(16, 0));
+    }
+}
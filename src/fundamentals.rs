@@ -1,60 +1,1187 @@
 use super::datetime::eodhd_serde_opt_date;
+use crate::{
+    eodhd_opt_string_float, eodhd_opt_string_int, eodhd_string_int,
+    rate_limit::EODHDEndpoint,
+    ticker::{parse_ticker, EODHDTicker},
+    EODHDClient, EODHDError,
+};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Display};
+
+/// Fundamentals of whole exchanges
+pub mod bulk;
+/// Typed income statements, balance
+/// sheets and cash flows
+pub mod financials;
+
+/// Documents of ETFs, mutual funds and indices
+pub mod funds;
+
+pub use financials::{EODHDFinancials, EODHDStatement};
+pub use funds::{EODHDETFFundamentals, EODHDFundFundamentals, EODHDIndexFundamentals};
+
+/// EODHD's fundamentals differ completely
+/// depending on the type of the instrument
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum EODHDAnyFundamentals {
+    Stock(Box<EODHDFundamentalsDocument>),
+    ETF(Box<EODHDETFFundamentals>),
+    Fund(Box<EODHDFundFundamentals>),
+    Index(Box<EODHDIndexFundamentals>),
+}
+
+impl EODHDAnyFundamentals {
+    /// Picks the variant by `General::Type`, falling back
+    /// to the presence of `ETF_Data`/`MutualFund_Data`
+    /// (e.g. for filtered documents without General)
+    pub fn from_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        Self::from_value_ref(&value)
+    }
+
+    /// Same as [EODHDAnyFundamentals::from_value],
+    /// keeping `value` e.g. for error messages
+    pub(crate) fn from_value_ref(value: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        let type_instrument = value
+            .pointer("/General/Type")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_uppercase());
+        let kind = match type_instrument.as_deref() {
+            Some("ETF") => "ETF",
+            Some("FUND") | Some("MUTUAL FUND") => "FUND",
+            Some("INDEX") => "INDEX",
+            Some(_) => "STOCK",
+            None if value.get("ETF_Data").is_some() => "ETF",
+            None if value.get("MutualFund_Data").is_some() => "FUND",
+            None if value.get("Components").is_some() => "INDEX",
+            None => "STOCK",
+        };
+        Ok(match kind {
+            "ETF" => EODHDAnyFundamentals::ETF(Box::new(Deserialize::deserialize(value)?)),
+            "FUND" => EODHDAnyFundamentals::Fund(Box::new(Deserialize::deserialize(value)?)),
+            "INDEX" => EODHDAnyFundamentals::Index(Box::new(Deserialize::deserialize(value)?)),
+            _ => EODHDAnyFundamentals::Stock(Box::new(Deserialize::deserialize(value)?)),
+        })
+    }
+
+    pub fn general(&self) -> Option<&EODHDGeneral> {
+        match self {
+            EODHDAnyFundamentals::Stock(d) => d.general.as_ref(),
+            EODHDAnyFundamentals::ETF(d) => d.general.as_ref(),
+            EODHDAnyFundamentals::Fund(d) => d.general.as_ref(),
+            EODHDAnyFundamentals::Index(d) => d.general.as_ref(),
+        }
+    }
+
+    pub fn as_stock(&self) -> Option<&EODHDFundamentalsDocument> {
+        match self {
+            EODHDAnyFundamentals::Stock(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    pub fn as_etf(&self) -> Option<&EODHDETFFundamentals> {
+        match self {
+            EODHDAnyFundamentals::ETF(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    pub fn as_fund(&self) -> Option<&EODHDFundFundamentals> {
+        match self {
+            EODHDAnyFundamentals::Fund(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    pub fn as_index(&self) -> Option<&EODHDIndexFundamentals> {
+        match self {
+            EODHDAnyFundamentals::Index(d) => Some(d),
+            _ => None,
+        }
+    }
+}
+
+/// The complete fundamentals of a stock,
+/// every section is optional as EODHD
+/// leaves out what it doesn't know.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDFundamentalsDocument {
+    #[serde(rename = "General", default)]
+    pub general: Option<EODHDGeneral>,
+    #[serde(rename = "Highlights", default)]
+    pub highlights: Option<EODHDFundamentals>,
+    #[serde(rename = "Valuation", default)]
+    pub valuation: Option<EODHDValuation>,
+    #[serde(rename = "SharesStats", default)]
+    pub shares_stats: Option<EODHDSharesStats>,
+    #[serde(rename = "Technicals", default)]
+    pub technicals: Option<EODHDTechnicals>,
+    #[serde(rename = "SplitsDividends", default)]
+    pub splits_dividends: Option<EODHDSplitsDividends>,
+    #[serde(rename = "AnalystRatings", default)]
+    pub analyst_ratings: Option<EODHDAnalystRatings>,
+    #[serde(rename = "Holders", default)]
+    pub holders: Option<EODHDHolders>,
+    #[serde(rename = "InsiderTransactions", default, with = "eodhd_indexed_vec")]
+    pub insider_transactions: Vec<EODHDInsiderTransaction>,
+    #[serde(rename = "ESGScores", default)]
+    pub esg_scores: Option<EODHDESGScores>,
+    #[serde(rename = "Earnings", default)]
+    pub earnings: Option<EODHDFundamentalsEarnings>,
+    #[serde(rename = "Financials", default)]
+    pub financials: Option<EODHDFinancials>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDGeneral {
+    #[serde(rename = "Code")]
+    pub code: Option<String>,
+    /// e.g. "Common Stock", "ETF", "FUND"
+    #[serde(rename = "Type")]
+    pub type_instrument: Option<String>,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "Exchange")]
+    pub exchange: Option<String>,
+    #[serde(rename = "CurrencyCode")]
+    pub currency_code: Option<String>,
+    #[serde(rename = "CurrencyName")]
+    pub currency_name: Option<String>,
+    #[serde(rename = "CurrencySymbol")]
+    pub currency_symbol: Option<String>,
+    #[serde(rename = "CountryName")]
+    pub country_name: Option<String>,
+    #[serde(rename = "CountryISO")]
+    pub country_iso: Option<String>,
+    #[serde(rename = "OpenFigi")]
+    pub open_figi: Option<String>,
+    #[serde(rename = "ISIN")]
+    pub isin: Option<String>,
+    #[serde(rename = "LEI")]
+    pub lei: Option<String>,
+    #[serde(rename = "CUSIP")]
+    pub cusip: Option<String>,
+    #[serde(rename = "CIK")]
+    pub cik: Option<String>,
+    #[serde(rename = "PrimaryTicker")]
+    pub primary_ticker: Option<String>,
+    #[serde(rename = "FiscalYearEnd")]
+    pub fiscal_year_end: Option<String>,
+    #[serde(rename = "IPODate", default, with = "eodhd_serde_opt_date")]
+    pub ipo_date: Option<NaiveDate>,
+    #[serde(rename = "InternationalDomestic")]
+    pub international_domestic: Option<String>,
+    #[serde(rename = "Sector")]
+    pub sector: Option<String>,
+    #[serde(rename = "Industry")]
+    pub industry: Option<String>,
+    #[serde(rename = "GicSector")]
+    pub gic_sector: Option<String>,
+    #[serde(rename = "GicGroup")]
+    pub gic_group: Option<String>,
+    #[serde(rename = "GicIndustry")]
+    pub gic_industry: Option<String>,
+    #[serde(rename = "GicSubIndustry")]
+    pub gic_sub_industry: Option<String>,
+    #[serde(rename = "HomeCategory")]
+    pub home_category: Option<String>,
+    #[serde(rename = "IsDelisted")]
+    pub is_delisted: Option<bool>,
+    #[serde(rename = "Description")]
+    pub description: Option<String>,
+    #[serde(rename = "Address")]
+    pub address: Option<String>,
+    #[serde(rename = "Phone")]
+    pub phone: Option<String>,
+    #[serde(rename = "WebURL")]
+    pub web_url: Option<String>,
+    #[serde(rename = "LogoURL")]
+    pub logo_url: Option<String>,
+    #[serde(rename = "FullTimeEmployees", default, with = "eodhd_opt_string_int")]
+    pub full_time_employees: Option<u64>,
+    #[serde(rename = "UpdatedAt", default, with = "eodhd_serde_opt_date")]
+    pub updated_at: Option<NaiveDate>,
+}
 
 /// EODHDFundamentals type
 /// has way to many fields that
 /// are mostly optional.
 /// Hard to query therefore get functions
 /// on API currently provided
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// (the Highlights section of [EODHDFundamentalsDocument])
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EODHDFundamentals {
-    #[serde(rename = "MarketCapitalization")]
+    #[serde(
+        rename = "MarketCapitalization",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub market_cap: Option<f64>,
-    #[serde(rename = "EBITDA")]
-    pub ebitda: Option<String>,
-    #[serde(rename = "PERatio")]
+    #[serde(rename = "EBITDA", default, with = "eodhd_opt_string_float")]
+    pub ebitda: Option<f64>,
+    #[serde(
+        rename = "PERatio",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub pe_ratio: Option<f64>,
-    #[serde(rename = "PEGRatio")]
+    #[serde(
+        rename = "PEGRatio",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub peg_ratio: Option<f64>,
-    #[serde(rename = "WallStreetTargetPrice")]
+    #[serde(
+        rename = "WallStreetTargetPrice",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub wallstreet_target_price: Option<f64>,
-    #[serde(rename = "BookValue")]
+    #[serde(
+        rename = "BookValue",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub book_value: Option<f64>,
-    #[serde(rename = "DividendShare")]
+    #[serde(
+        rename = "DividendShare",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub dividend_share: Option<f64>,
-    #[serde(rename = "DividendYield")]
+    #[serde(
+        rename = "DividendYield",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub dividend_yield: Option<f64>,
-    #[serde(rename = "EarningsShare")]
+    #[serde(
+        rename = "EarningsShare",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub earnings_share: Option<f64>,
-    #[serde(rename = "EPSEstimateCurrentYear")]
+    #[serde(
+        rename = "EPSEstimateCurrentYear",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub eps_estimate_current_year: Option<f64>,
-    #[serde(rename = "EPSEstimateNextYear")]
+    #[serde(
+        rename = "EPSEstimateNextYear",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub eps_estimate_next_year: Option<f64>,
-    #[serde(rename = "EPSEstimateNextQuarter")]
+    #[serde(
+        rename = "EPSEstimateNextQuarter",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub eps_estimate_next_quarter: Option<f64>,
-    #[serde(rename = "EPSEstimateCurrentQuarter")]
+    #[serde(
+        rename = "EPSEstimateCurrentQuarter",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub eps_estimate_current_quarter: Option<f64>,
-    #[serde(rename = "MostRecentQuarter", with = "eodhd_serde_opt_date")]
+    #[serde(rename = "MostRecentQuarter", default, with = "eodhd_serde_opt_date")]
     pub most_recent_quarter: Option<NaiveDate>,
-    #[serde(rename = "ProfitMargin")]
+    #[serde(
+        rename = "ProfitMargin",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub profit_margin: Option<f64>,
-    #[serde(rename = "OperatingMarginTTM")]
+    #[serde(
+        rename = "OperatingMarginTTM",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub operating_margin_ttm: Option<f64>,
-    #[serde(rename = "ReturnOnAssetsTTM")]
+    #[serde(
+        rename = "ReturnOnAssetsTTM",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub return_on_assets_ttm: Option<f64>,
-    #[serde(rename = "ReturnOnEquityTTM")]
+    #[serde(
+        rename = "ReturnOnEquityTTM",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub return_on_equity_ttm: Option<f64>,
-    #[serde(rename = "RevenueTTM")]
+    #[serde(
+        rename = "RevenueTTM",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub revenue_ttm: Option<f64>,
-    #[serde(rename = "RevenuePerShareTTM")]
+    #[serde(
+        rename = "RevenuePerShareTTM",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub revenue_per_share_ttm: Option<f64>,
-    #[serde(rename = "QuarterlyRevenueGrowthYOY")]
+    #[serde(
+        rename = "QuarterlyRevenueGrowthYOY",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub quarterly_revenue_growth_yoy: Option<f64>,
-    #[serde(rename = "GrossProfitTTM")]
+    #[serde(
+        rename = "GrossProfitTTM",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub gross_profit_ttm: Option<f64>,
-    #[serde(rename = "DilutedEpsTTM")]
+    #[serde(
+        rename = "DilutedEpsTTM",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub diluted_eps_ttm: Option<f64>,
-    #[serde(rename = "QuarterlyEarningsGrowthYOY")]
+    #[serde(
+        rename = "QuarterlyEarningsGrowthYOY",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
     pub quarterly_earnings_growth_yoy: Option<f64>,
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDValuation {
+    #[serde(
+        rename = "TrailingPE",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub trailing_pe: Option<f64>,
+    #[serde(
+        rename = "ForwardPE",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub forward_pe: Option<f64>,
+    #[serde(
+        rename = "PriceSalesTTM",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub price_sales_ttm: Option<f64>,
+    #[serde(
+        rename = "PriceBookMRQ",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub price_book_mrq: Option<f64>,
+    #[serde(
+        rename = "EnterpriseValue",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub enterprise_value: Option<f64>,
+    #[serde(
+        rename = "EnterpriseValueRevenue",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub enterprise_value_revenue: Option<f64>,
+    #[serde(
+        rename = "EnterpriseValueEbitda",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub enterprise_value_ebitda: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDSharesStats {
+    #[serde(
+        rename = "SharesOutstanding",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub shares_outstanding: Option<f64>,
+    #[serde(
+        rename = "SharesFloat",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub shares_float: Option<f64>,
+    #[serde(
+        rename = "PercentInsiders",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub percent_insiders: Option<f64>,
+    #[serde(
+        rename = "PercentInstitutions",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub percent_institutions: Option<f64>,
+    #[serde(
+        rename = "SharesShort",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub shares_short: Option<f64>,
+    #[serde(
+        rename = "SharesShortPriorMonth",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub shares_short_prior_month: Option<f64>,
+    #[serde(
+        rename = "ShortRatio",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub short_ratio: Option<f64>,
+    #[serde(
+        rename = "ShortPercentOutstanding",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub short_percent_outstanding: Option<f64>,
+    #[serde(
+        rename = "ShortPercentFloat",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub short_percent_float: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDTechnicals {
+    #[serde(
+        rename = "Beta",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub beta: Option<f64>,
+    #[serde(
+        rename = "52WeekHigh",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub week_52_high: Option<f64>,
+    #[serde(
+        rename = "52WeekLow",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub week_52_low: Option<f64>,
+    #[serde(
+        rename = "50DayMA",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub day_50_ma: Option<f64>,
+    #[serde(
+        rename = "200DayMA",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub day_200_ma: Option<f64>,
+    #[serde(
+        rename = "SharesShort",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub shares_short: Option<f64>,
+    #[serde(
+        rename = "SharesShortPriorMonth",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub shares_short_prior_month: Option<f64>,
+    #[serde(
+        rename = "ShortRatio",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub short_ratio: Option<f64>,
+    #[serde(
+        rename = "ShortPercent",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub short_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDSplitsDividends {
+    #[serde(
+        rename = "ForwardAnnualDividendRate",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub forward_annual_dividend_rate: Option<f64>,
+    #[serde(
+        rename = "ForwardAnnualDividendYield",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub forward_annual_dividend_yield: Option<f64>,
+    #[serde(
+        rename = "PayoutRatio",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub payout_ratio: Option<f64>,
+    #[serde(rename = "DividendDate", default, with = "eodhd_serde_opt_date")]
+    pub dividend_date: Option<NaiveDate>,
+    #[serde(rename = "ExDividendDate", default, with = "eodhd_serde_opt_date")]
+    pub ex_dividend_date: Option<NaiveDate>,
+    /// e.g. "4:1"
+    #[serde(rename = "LastSplitFactor")]
+    pub last_split_factor: Option<String>,
+    #[serde(rename = "LastSplitDate", default, with = "eodhd_serde_opt_date")]
+    pub last_split_date: Option<NaiveDate>,
+    #[serde(rename = "NumberDividendsByYear", default, with = "eodhd_indexed_vec")]
+    pub number_dividends_by_year: Vec<EODHDDividendsPerYear>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDDividendsPerYear {
+    #[serde(rename = "Year", with = "eodhd_string_int")]
+    pub year: i32,
+    #[serde(rename = "Count", with = "eodhd_string_int")]
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDAnalystRatings {
+    /// 1 (strong sell) to 5 (strong buy)
+    #[serde(
+        rename = "Rating",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub rating: Option<f64>,
+    #[serde(
+        rename = "TargetPrice",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub target_price: Option<f64>,
+    #[serde(rename = "StrongBuy", default, with = "eodhd_opt_string_int")]
+    pub strong_buy: Option<u32>,
+    #[serde(rename = "Buy", default, with = "eodhd_opt_string_int")]
+    pub buy: Option<u32>,
+    #[serde(rename = "Hold", default, with = "eodhd_opt_string_int")]
+    pub hold: Option<u32>,
+    #[serde(rename = "Sell", default, with = "eodhd_opt_string_int")]
+    pub sell: Option<u32>,
+    #[serde(rename = "StrongSell", default, with = "eodhd_opt_string_int")]
+    pub strong_sell: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDHolders {
+    #[serde(rename = "Institutions", default, with = "eodhd_indexed_vec")]
+    pub institutions: Vec<EODHDHolder>,
+    #[serde(rename = "Funds", default, with = "eodhd_indexed_vec")]
+    pub funds: Vec<EODHDHolder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDHolder {
+    pub name: String,
+    #[serde(default, with = "eodhd_serde_opt_date")]
+    pub date: Option<NaiveDate>,
+    /// percentage of all shares
+    #[serde(
+        rename = "totalShares",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub total_shares: Option<f64>,
+    /// percentage of the holder's assets
+    #[serde(
+        rename = "totalAssets",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub total_assets: Option<f64>,
+    #[serde(
+        rename = "currentShares",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub current_shares: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_p: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDInsiderTransaction {
+    #[serde(default, with = "eodhd_serde_opt_date")]
+    pub date: Option<NaiveDate>,
+    #[serde(rename = "ownerName")]
+    pub owner_name: Option<String>,
+    #[serde(rename = "transactionDate", default, with = "eodhd_serde_opt_date")]
+    pub transaction_date: Option<NaiveDate>,
+    /// SEC transaction code, e.g. "S" for a sale
+    #[serde(rename = "transactionCode")]
+    pub transaction_code: Option<String>,
+    #[serde(
+        rename = "transactionAmount",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub transaction_amount: Option<f64>,
+    #[serde(
+        rename = "transactionPrice",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub transaction_price: Option<f64>,
+    /// "A" acquired or "D" disposed
+    #[serde(rename = "transactionAcquiredDisposed")]
+    pub transaction_acquired_disposed: Option<String>,
+    #[serde(
+        rename = "postTransactionAmount",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub post_transaction_amount: Option<f64>,
+    #[serde(rename = "secLink")]
+    pub sec_link: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDESGScores {
+    #[serde(rename = "RatingDate", default, with = "eodhd_serde_opt_date")]
+    pub rating_date: Option<NaiveDate>,
+    #[serde(
+        rename = "TotalEsg",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub total_esg: Option<f64>,
+    #[serde(
+        rename = "TotalEsgPercentile",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub total_esg_percentile: Option<f64>,
+    #[serde(
+        rename = "EnvironmentScore",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub environment_score: Option<f64>,
+    #[serde(
+        rename = "EnvironmentScorePercentile",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub environment_score_percentile: Option<f64>,
+    #[serde(
+        rename = "SocialScore",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub social_score: Option<f64>,
+    #[serde(
+        rename = "SocialScorePercentile",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub social_score_percentile: Option<f64>,
+    #[serde(
+        rename = "GovernanceScore",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub governance_score: Option<f64>,
+    #[serde(
+        rename = "GovernanceScorePercentile",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub governance_score_percentile: Option<f64>,
+    #[serde(
+        rename = "ControversyLevel",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub controversy_level: Option<f64>,
+}
+
+/// Earnings section of the fundamentals,
+/// all maps are keyed by date (`%Y-%m-%d`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDFundamentalsEarnings {
+    #[serde(rename = "History", default)]
+    pub history: BTreeMap<String, EODHDEarningsHistory>,
+    #[serde(rename = "Trend", default)]
+    pub trend: BTreeMap<String, EODHDEarningsTrend>,
+    #[serde(rename = "Annual", default)]
+    pub annual: BTreeMap<String, EODHDEarningsAnnual>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDEarningsHistory {
+    #[serde(rename = "reportDate", default, with = "eodhd_serde_opt_date")]
+    pub report_date: Option<NaiveDate>,
+    #[serde(default, with = "eodhd_serde_opt_date")]
+    pub date: Option<NaiveDate>,
+    #[serde(rename = "beforeAfterMarket")]
+    pub before_after_market: Option<String>,
+    pub currency: Option<String>,
+    #[serde(
+        rename = "epsActual",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_actual: Option<f64>,
+    #[serde(
+        rename = "epsEstimate",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_estimate: Option<f64>,
+    #[serde(
+        rename = "epsDifference",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_difference: Option<f64>,
+    #[serde(
+        rename = "surprisePercent",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub surprise_percent: Option<f64>,
+}
+
+/// Analyst estimates, EODHD encodes
+/// most of the numbers as strings here
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDEarningsTrend {
+    #[serde(default, with = "eodhd_serde_opt_date")]
+    pub date: Option<NaiveDate>,
+    /// e.g. "0q", "+1q", "0y", "+1y"
+    pub period: Option<String>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub growth: Option<f64>,
+    #[serde(
+        rename = "earningsEstimateAvg",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub earnings_estimate_avg: Option<f64>,
+    #[serde(
+        rename = "earningsEstimateLow",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub earnings_estimate_low: Option<f64>,
+    #[serde(
+        rename = "earningsEstimateHigh",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub earnings_estimate_high: Option<f64>,
+    #[serde(
+        rename = "earningsEstimateYearAgoEps",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub earnings_estimate_year_ago_eps: Option<f64>,
+    #[serde(
+        rename = "earningsEstimateNumberOfAnalysts",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub earnings_estimate_number_of_analysts: Option<f64>,
+    #[serde(
+        rename = "earningsEstimateGrowth",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub earnings_estimate_growth: Option<f64>,
+    #[serde(
+        rename = "revenueEstimateAvg",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub revenue_estimate_avg: Option<f64>,
+    #[serde(
+        rename = "revenueEstimateLow",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub revenue_estimate_low: Option<f64>,
+    #[serde(
+        rename = "revenueEstimateHigh",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub revenue_estimate_high: Option<f64>,
+    #[serde(
+        rename = "revenueEstimateYearAgoEps",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub revenue_estimate_year_ago_eps: Option<f64>,
+    #[serde(
+        rename = "revenueEstimateNumberOfAnalysts",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub revenue_estimate_number_of_analysts: Option<f64>,
+    #[serde(
+        rename = "revenueEstimateGrowth",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub revenue_estimate_growth: Option<f64>,
+    #[serde(
+        rename = "epsTrendCurrent",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_trend_current: Option<f64>,
+    #[serde(
+        rename = "epsTrend7daysAgo",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_trend_7_days_ago: Option<f64>,
+    #[serde(
+        rename = "epsTrend30daysAgo",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_trend_30_days_ago: Option<f64>,
+    #[serde(
+        rename = "epsTrend60daysAgo",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_trend_60_days_ago: Option<f64>,
+    #[serde(
+        rename = "epsTrend90daysAgo",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_trend_90_days_ago: Option<f64>,
+    #[serde(
+        rename = "epsRevisionsUpLast7days",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_revisions_up_last_7_days: Option<f64>,
+    #[serde(
+        rename = "epsRevisionsUpLast30days",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_revisions_up_last_30_days: Option<f64>,
+    #[serde(
+        rename = "epsRevisionsDownLast30days",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_revisions_down_last_30_days: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDEarningsAnnual {
+    #[serde(default, with = "eodhd_serde_opt_date")]
+    pub date: Option<NaiveDate>,
+    #[serde(
+        rename = "epsActual",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub eps_actual: Option<f64>,
+}
+
+/// Top level sections of [EODHDFundamentalsDocument]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDFundamentalsSection {
+    General,
+    Highlights,
+    Valuation,
+    SharesStats,
+    Technicals,
+    SplitsDividends,
+    AnalystRatings,
+    Holders,
+    InsiderTransactions,
+    ESGScores,
+    Earnings,
+    Financials,
+}
+
+impl Display for EODHDFundamentalsSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EODHDFundamentalsSection::General => "General",
+            EODHDFundamentalsSection::Highlights => "Highlights",
+            EODHDFundamentalsSection::Valuation => "Valuation",
+            EODHDFundamentalsSection::SharesStats => "SharesStats",
+            EODHDFundamentalsSection::Technicals => "Technicals",
+            EODHDFundamentalsSection::SplitsDividends => "SplitsDividends",
+            EODHDFundamentalsSection::AnalystRatings => "AnalystRatings",
+            EODHDFundamentalsSection::Holders => "Holders",
+            EODHDFundamentalsSection::InsiderTransactions => "InsiderTransactions",
+            EODHDFundamentalsSection::ESGScores => "ESGScores",
+            EODHDFundamentalsSection::Earnings => "Earnings",
+            EODHDFundamentalsSection::Financials => "Financials",
+        })
+    }
+}
+
+/// Builds the `filter=` parameter of the fundamentals
+/// endpoint, e.g.
+/// `General::Code,Highlights,Financials::Balance_Sheet::yearly`
+/// so only the selected parts of the document are transferred.
+#[derive(Debug, Clone, Default)]
+pub struct EODHDFundamentalsFilter {
+    paths: Vec<String>,
+}
+
+impl EODHDFundamentalsFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests a whole section
+    pub fn section(self, section: EODHDFundamentalsSection) -> Self {
+        self.path(&[&section.to_string()])
+    }
+
+    /// Requests a single field of a section
+    /// by its EODHD name, e.g. `"Code"` of General
+    pub fn field(self, section: EODHDFundamentalsSection, field: &str) -> Self {
+        self.path(&[&section.to_string(), field])
+    }
+
+    /// Requests an arbitrary nested part of the document,
+    /// e.g. `&["Financials", "Balance_Sheet", "yearly"]`
+    pub fn path(mut self, segments: &[&str]) -> Self {
+        let path = segments.join("::");
+        if !self.paths.contains(&path) {
+            self.paths.push(path);
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Puts the parts of a filtered response back to where
+    /// they belong in the full document. EODHD answers a single
+    /// path with the bare value and multiple paths with an
+    /// object keyed by the paths.
+    fn nest(&self, response: serde_json::Value) -> serde_json::Value {
+        let mut document = serde_json::Value::Object(serde_json::Map::new());
+        if self.paths.len() == 1 {
+            insert_path(&mut document, &self.paths[0], response);
+            return document;
+        }
+        if let serde_json::Value::Object(mut parts) = response {
+            for path in &self.paths {
+                if let Some(part) = parts.remove(path) {
+                    insert_path(&mut document, path, part);
+                }
+            }
+        }
+        document
+    }
+}
+
+impl Display for EODHDFundamentalsFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.paths.join(","))
+    }
+}
+
+fn insert_path(document: &mut serde_json::Value, path: &str, value: serde_json::Value) {
+    let mut current = document;
+    let mut segments = path.split("::").peekable();
+    while let Some(segment) = segments.next() {
+        if !current.is_object() {
+            *current = serde_json::Value::Object(serde_json::Map::new());
+        }
+        let object = current.as_object_mut().unwrap();
+        if segments.peek().is_none() {
+            object.insert(segment.to_string(), value);
+            return;
+        }
+        current = object
+            .entry(segment.to_string())
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+    }
+}
+
+impl EODHDClient {
+    /// Fetches the whole fundamentals document
+    /// (costs 10 api calls).
+    pub async fn get_fundamentals(
+        &self,
+        ticker: &EODHDTicker,
+    ) -> Result<EODHDAnyFundamentals, EODHDError> {
+        self.get_fundamentals_filtered(ticker, &EODHDFundamentalsFilter::new())
+            .await
+    }
+
+    /// Fetches only the parts of the fundamentals selected by
+    /// `filter`, everything not requested stays `None`/empty
+    /// in the returned document.
+    pub async fn get_fundamentals_filtered(
+        &self,
+        ticker: &EODHDTicker,
+        filter: &EODHDFundamentalsFilter,
+    ) -> Result<EODHDAnyFundamentals, EODHDError> {
+        let mut query = vec![];
+        if !filter.is_empty() {
+            query.push(("filter", filter.to_string()));
+        }
+        let (url, body) = self
+            .get_ticker_text(
+                EODHDEndpoint::Fundamentals,
+                ticker,
+                &format!("fundamentals/{}", ticker.path_segment()),
+                &query,
+            )
+            .await?;
+        let mut response = serde_json::from_str::<serde_json::Value>(&body)
+            .map_err(|e| EODHDError::decode(&url, &body, e))?;
+        if !filter.is_empty() {
+            response = filter.nest(response);
+        }
+        EODHDAnyFundamentals::from_value(response).map_err(|e| EODHDError::decode(&url, &body, e))
+    }
+}
+
+/// Shorthand for [EODHDClient::get_fundamentals]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_fundamentals(ticker: &str) -> Result<EODHDAnyFundamentals, EODHDError> {
+    EODHDClient::from_env()?
+        .get_fundamentals(&parse_ticker(ticker)?)
+        .await
+}
+
+/// EODHD encodes lists in fundamentals as objects
+/// keyed by their index ("0", "1", ...), this
+/// turns them into a `Vec` (in index order).
+/// Arrays and `null` are accepted as well.
+pub mod eodhd_indexed_vec {
+    use serde::{self, de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Indexed<T> {
+        Array(Vec<T>),
+        Map(BTreeMap<String, T>),
+    }
+
+    pub fn serialize<S, T>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        let indexed: BTreeMap<String, &T> = value
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect();
+        indexed.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: DeserializeOwned,
+    {
+        match Option::<Indexed<T>>::deserialize(deserializer)? {
+            Some(Indexed::Array(values)) => Ok(values),
+            Some(Indexed::Map(map)) => {
+                let mut indexed: Vec<(usize, T)> = map
+                    .into_iter()
+                    .map(|(k, v)| (k.parse().unwrap_or(usize::MAX), v))
+                    .collect();
+                indexed.sort_by_key(|(i, _)| *i);
+                Ok(indexed.into_iter().map(|(_, v)| v).collect())
+            }
+            None => Ok(vec![]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn filter_paths() {
+        let filter = EODHDFundamentalsFilter::new()
+            .field(EODHDFundamentalsSection::General, "Code")
+            .section(EODHDFundamentalsSection::Highlights)
+            .path(&["Financials", "Balance_Sheet", "yearly"])
+            .section(EODHDFundamentalsSection::Highlights);
+        assert_eq!(
+            filter.to_string(),
+            "General::Code,Highlights,Financials::Balance_Sheet::yearly"
+        );
+        assert!(EODHDFundamentalsFilter::new().is_empty());
+    }
+
+    #[test]
+    fn nest_single_path() {
+        let filter =
+            EODHDFundamentalsFilter::new().path(&["Financials", "Balance_Sheet", "yearly"]);
+        assert_eq!(
+            filter.nest(json!({"2022-09-30": {"totalAssets": "1"}})),
+            json!({"Financials": {"Balance_Sheet": {"yearly": {"2022-09-30": {"totalAssets": "1"}}}}})
+        );
+        let filter =
+            EODHDFundamentalsFilter::new().field(EODHDFundamentalsSection::General, "Code");
+        assert_eq!(
+            filter.nest(json!("AAPL")),
+            json!({"General": {"Code": "AAPL"}})
+        );
+    }
+
+    #[test]
+    fn nest_multiple_paths() {
+        let filter = EODHDFundamentalsFilter::new()
+            .field(EODHDFundamentalsSection::General, "Code")
+            .field(EODHDFundamentalsSection::General, "Name")
+            .section(EODHDFundamentalsSection::Highlights)
+            .path(&["Financials", "Cash_Flow", "quarterly"]);
+        let nested = filter.nest(json!({
+            "General::Code": "AAPL",
+            "General::Name": "Apple Inc",
+            "Highlights": {"MarketCapitalization": 1},
+            "Unrequested": 1
+        }));
+        assert_eq!(
+            nested,
+            json!({
+                "General": {"Code": "AAPL", "Name": "Apple Inc"},
+                "Highlights": {"MarketCapitalization": 1}
+            })
+        );
+        let document = EODHDAnyFundamentals::from_value(nested).unwrap();
+        assert_eq!(
+            document.general().and_then(|g| g.code.as_deref()),
+            Some("AAPL")
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::VecDeque;
+
+use futures_util::{stream, Stream};
+use log::warn;
+
+use super::EODHDAnyFundamentals;
+use crate::{rate_limit::EODHDEndpoint, ticker::EODHDTicker, EODHDClient, EODHDError};
+
+/// Maximum page size EODHD accepts
+const MAX_LIMIT: u32 = 500;
+
+pub struct EODHDBulkFundamentalsOptions {
+    /// Only fetch these tickers (e.g. "AAPL.US"),
+    /// pagination is skipped in that case.
+    pub symbols: Option<Vec<EODHDTicker>>,
+    /// Index of the first ticker of the exchange
+    pub offset: u32,
+    /// Tickers per request, at most 500
+    pub limit: u32,
+}
+
+impl Default for EODHDBulkFundamentalsOptions {
+    fn default() -> Self {
+        Self {
+            symbols: None,
+            offset: 0,
+            limit: MAX_LIMIT,
+        }
+    }
+}
+
+struct BulkState {
+    client: EODHDClient,
+    exchange: String,
+    options: EODHDBulkFundamentalsOptions,
+    buffer: VecDeque<Result<EODHDAnyFundamentals, EODHDError>>,
+    finished: bool,
+    // code of the first document of the last page
+    first_code: Option<String>,
+}
+
+/// A decoded page and the code of its first document
+type BulkPage = (
+    Option<String>,
+    Vec<Result<EODHDAnyFundamentals, EODHDError>>,
+);
+
+impl EODHDClient {
+    /// Streams the fundamentals of every ticker of `exchange`,
+    /// requesting pages of `options.limit` tickers (each request
+    /// costs 100 api calls) until EODHD returns a short page,
+    /// an empty one or the previous one again (it ignores the
+    /// offset for some exchanges).
+    /// A document that can't be decoded is yielded as error
+    /// without ending the stream, failed requests end it.
+    /// The bulk layout of earnings and financials is mapped
+    /// to the one of [EODHDClient::get_fundamentals].
+    pub fn get_bulk_fundamentals(
+        &self,
+        exchange: &str,
+        options: EODHDBulkFundamentalsOptions,
+    ) -> impl Stream<Item = Result<EODHDAnyFundamentals, EODHDError>> {
+        let state = BulkState {
+            client: self.clone(),
+            exchange: exchange.to_string(),
+            options: EODHDBulkFundamentalsOptions {
+                limit: options.limit.clamp(1, MAX_LIMIT),
+                ..options
+            },
+            buffer: VecDeque::new(),
+            finished: false,
+            first_code: None,
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(document) = state.buffer.pop_front() {
+                    return Some((document, state));
+                }
+                if state.finished {
+                    return None;
+                }
+                match state.fetch_page().await {
+                    Ok(page) => state.add_page(page),
+                    Err(e) => {
+                        state.finished = true;
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl BulkState {
+    fn add_page(&mut self, (first_code, page): BulkPage) {
+        if page.is_empty() {
+            self.finished = true;
+            return;
+        }
+        if first_code.is_some() && first_code == self.first_code {
+            warn!(
+                "bulk fundamentals of {} repeat the page at offset {}, stopping",
+                self.exchange, self.options.offset
+            );
+            self.finished = true;
+            return;
+        }
+        self.finished = self.options.symbols.is_some() || page.len() < self.options.limit as usize;
+        self.options.offset += self.options.limit;
+        self.first_code = first_code;
+        self.buffer.extend(page);
+    }
+
+    async fn fetch_page(&self) -> Result<BulkPage, EODHDError> {
+        let mut query = vec![];
+        match &self.options.symbols {
+            Some(symbols) => query.push((
+                "symbols",
+                symbols
+                    .iter()
+                    .map(EODHDTicker::to_string)
+                    .collect::<Vec<String>>()
+                    .join(","),
+            )),
+            None => {
+                query.push(("offset", self.options.offset.to_string()));
+                query.push(("limit", self.options.limit.to_string()));
+            }
+        }
+        let (url, body) = self
+            .client
+            .get_text(
+                EODHDEndpoint::BulkFundamentals,
+                &format!("bulk-fundamentals/{}", self.exchange),
+                &query,
+            )
+            .await?;
+        // an object keyed by index ("0", "1", ...) or an array
+        let documents: Vec<serde_json::Value> =
+            match serde_json::from_str::<serde_json::Value>(&body)
+                .map_err(|e| EODHDError::decode(&url, &body, e))?
+            {
+                serde_json::Value::Array(documents) => documents,
+                serde_json::Value::Object(documents) => {
+                    let mut indexed: Vec<(usize, serde_json::Value)> = documents
+                        .into_iter()
+                        .map(|(k, d)| (k.parse().unwrap_or(usize::MAX), d))
+                        .collect();
+                    indexed.sort_by_key(|(i, _)| *i);
+                    indexed.into_iter().map(|(_, d)| d).collect()
+                }
+                _ => vec![],
+            };
+        let first_code = documents.first().and_then(document_code);
+        let page = documents
+            .into_iter()
+            .map(|document| {
+                let document = from_bulk(document);
+                EODHDAnyFundamentals::from_value_ref(&document)
+                    .map_err(|e| EODHDError::decode(&url, &document.to_string(), e))
+            })
+            .collect();
+        Ok((first_code, page))
+    }
+}
+
+/// `General::Code`, also of documents that can't be decoded
+fn document_code(document: &serde_json::Value) -> Option<String> {
+    document
+        .pointer("/General/Code")
+        .and_then(|code| code.as_str())
+        .map(str::to_string)
+}
+
+/// Bulk documents list the last periods as `Earnings::Last_N`
+/// and `Financials::*::quarterly_last_N`/`yearly_last_N`
+/// instead of keying them by date, rearranges them
+/// like in the single ticker documents.
+fn from_bulk(mut document: serde_json::Value) -> serde_json::Value {
+    if let Some(earnings) = document.get_mut("Earnings") {
+        rekey(earnings, &[("Last_", "History")]);
+    }
+    if let Some(serde_json::Value::Object(financials)) = document.get_mut("Financials") {
+        for statement in financials.values_mut() {
+            rekey(
+                statement,
+                &[("quarterly_last_", "quarterly"), ("yearly_last_", "yearly")],
+            );
+        }
+    }
+    document
+}
+
+/// Moves the entries whose key starts with one of the
+/// `(prefix, section)` pairs into `section`, keyed by
+/// their `date` (or the original key without one)
+fn rekey(value: &mut serde_json::Value, sections: &[(&str, &str)]) {
+    let object = match value {
+        serde_json::Value::Object(object) => object,
+        _ => return,
+    };
+    for (prefix, section) in sections {
+        let keys = object
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect::<Vec<String>>();
+        if keys.is_empty() {
+            continue;
+        }
+        let mut periods = serde_json::Map::new();
+        for key in keys {
+            if let Some(period) = object.remove(&key) {
+                let date = period
+                    .get("date")
+                    .and_then(|date| date.as_str())
+                    .map(str::to_string)
+                    .unwrap_or(key);
+                periods.insert(date, period);
+            }
+        }
+        object.insert(section.to_string(), serde_json::Value::Object(periods));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shortened document of the bulk-fundamentals endpoint
+    const BULK_DOCUMENT: &str = r#"{
+        "General": {"Code": "AAPL", "Type": "Common Stock", "Exchange": "NASDAQ"},
+        "Earnings": {
+            "Last_0": {"reportDate": "2023-08-03", "date": "2023-06-30", "epsActual": 1.26, "epsEstimate": 1.19, "epsDifference": 0.07, "surprisePercent": 5.8824},
+            "Last_1": {"reportDate": "2023-05-04", "date": "2023-03-31", "epsActual": 1.52, "epsEstimate": 1.43, "epsDifference": 0.09, "surprisePercent": 6.2937}
+        },
+        "Financials": {
+            "Balance_Sheet": {
+                "currency_symbol": "USD",
+                "quarterly_last_0": {"date": "2023-06-30", "filing_date": "2023-08-04", "totalAssets": "335038000000.00"},
+                "quarterly_last_1": {"date": "2023-03-31", "filing_date": "2023-05-05", "totalAssets": "332160000000.00"},
+                "yearly_last_0": {"date": "2022-09-30", "filing_date": "2022-10-28", "totalAssets": "352755000000.00"}
+            },
+            "Cash_Flow": {
+                "currency_symbol": "USD",
+                "quarterly_last_0": {"date": "2023-06-30", "netIncome": "19881000000.00"}
+            },
+            "Income_Statement": {
+                "currency_symbol": "USD",
+                "yearly_last_0": {"date": "2022-09-30", "totalRevenue": "394328000000.00"}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn bulk_periods_are_keyed_by_date() {
+        let document = serde_json::from_str(BULK_DOCUMENT).unwrap();
+        let fundamentals = EODHDAnyFundamentals::from_value(from_bulk(document)).unwrap();
+        let stock = fundamentals.as_stock().unwrap();
+
+        let history = &stock.earnings.as_ref().unwrap().history;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history["2023-06-30"].eps_actual, Some(1.26));
+
+        let financials = stock.financials.as_ref().unwrap();
+        let balance_sheet = financials.balance_sheet.as_ref().unwrap();
+        assert_eq!(balance_sheet.currency_symbol.as_deref(), Some("USD"));
+        assert_eq!(balance_sheet.quarterly_series().len(), 2);
+        assert_eq!(
+            balance_sheet.quarterly["2023-03-31"].total_assets,
+            Some(332160000000.0)
+        );
+        assert_eq!(
+            balance_sheet.yearly["2022-09-30"].total_assets,
+            Some(352755000000.0)
+        );
+        let cash_flow = financials.cash_flow.as_ref().unwrap();
+        assert_eq!(
+            cash_flow.quarterly["2023-06-30"].net_income,
+            Some(19881000000.0)
+        );
+        let income_statement = financials.income_statement.as_ref().unwrap();
+        assert_eq!(
+            income_statement.yearly["2022-09-30"].total_revenue,
+            Some(394328000000.0)
+        );
+    }
+
+    #[test]
+    fn regular_documents_are_unchanged() {
+        let document = serde_json::json!({
+            "Earnings": {"History": {"2023-06-30": {"date": "2023-06-30"}}},
+            "Financials": {"Cash_Flow": {"quarterly": {"2023-06-30": {"date": "2023-06-30"}}}}
+        });
+        assert_eq!(from_bulk(document.clone()), document);
+    }
+
+    fn page(codes: &[&str]) -> BulkPage {
+        let documents = codes
+            .iter()
+            .map(|code| serde_json::json!({"General": {"Code": code, "Type": "Common Stock"}}))
+            .collect::<Vec<_>>();
+        let first = documents.first().and_then(document_code);
+        let page = documents
+            .into_iter()
+            .map(|document| Ok(EODHDAnyFundamentals::from_value(document).unwrap()))
+            .collect();
+        (first, page)
+    }
+
+    fn paging(limit: u32) -> BulkState {
+        BulkState {
+            client: EODHDClient::new("token"),
+            exchange: "US".to_string(),
+            options: EODHDBulkFundamentalsOptions {
+                limit,
+                ..Default::default()
+            },
+            buffer: VecDeque::new(),
+            finished: false,
+            first_code: None,
+        }
+    }
+
+    #[test]
+    fn pages_until_a_short_one() {
+        let mut state = paging(2);
+        state.add_page(page(&["A", "B"]));
+        assert!(!state.finished);
+        assert_eq!(state.options.offset, 2);
+        state.add_page(page(&["C"]));
+        assert!(state.finished);
+        assert_eq!(state.buffer.len(), 3);
+    }
+
+    #[test]
+    fn stops_on_an_empty_or_repeated_page() {
+        let mut state = paging(2);
+        state.add_page(page(&["A", "B"]));
+        // the offset was ignored
+        state.add_page(page(&["A", "B"]));
+        assert!(state.finished);
+        assert_eq!(state.buffer.len(), 2);
+
+        let mut state = paging(2);
+        state.add_page(page(&["A", "B"]));
+        state.add_page(page(&[]));
+        assert!(state.finished);
+        assert_eq!(state.buffer.len(), 2);
+    }
+}
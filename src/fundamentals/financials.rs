@@ -0,0 +1,594 @@
+use crate::{datetime::eodhd_serde_opt_date, eodhd_opt_string_float};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDFinancials {
+    #[serde(rename = "Balance_Sheet", default)]
+    pub balance_sheet: Option<EODHDStatement<EODHDBalanceSheet>>,
+    #[serde(rename = "Cash_Flow", default)]
+    pub cash_flow: Option<EODHDStatement<EODHDCashFlow>>,
+    #[serde(rename = "Income_Statement", default)]
+    pub income_statement: Option<EODHDStatement<EODHDIncomeStatement>>,
+}
+
+/// One financial statement, the periods are
+/// keyed by date (`%Y-%m-%d`) like EODHD does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDStatement<T> {
+    pub currency_symbol: Option<String>,
+    #[serde(default = "BTreeMap::new")]
+    pub quarterly: BTreeMap<String, T>,
+    #[serde(default = "BTreeMap::new")]
+    pub yearly: BTreeMap<String, T>,
+}
+
+impl<T> Default for EODHDStatement<T> {
+    fn default() -> Self {
+        Self {
+            currency_symbol: None,
+            quarterly: BTreeMap::new(),
+            yearly: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> EODHDStatement<T> {
+    /// Quarters sorted by date (oldest first),
+    /// periods with malformed dates are skipped
+    pub fn quarterly_series(&self) -> Vec<(NaiveDate, &T)> {
+        series(&self.quarterly)
+    }
+
+    /// Fiscal years sorted by date (oldest first),
+    /// periods with malformed dates are skipped
+    pub fn yearly_series(&self) -> Vec<(NaiveDate, &T)> {
+        series(&self.yearly)
+    }
+}
+
+impl<T: EODHDFlowStatement> EODHDStatement<T> {
+    /// Trailing twelve months, the sum of the last four
+    /// quarters dated like the latest one. `None` if there
+    /// are fewer than four quarters or they don't span a
+    /// single year, line items missing in any of the
+    /// quarters are `None` as well.
+    pub fn ttm(&self) -> Option<T> {
+        let quarterly = self.quarterly_series();
+        if quarterly.len() < 4 {
+            return None;
+        }
+        let last_four = &quarterly[quarterly.len() - 4..];
+        let span = last_four[3].0 - last_four[0].0;
+        if span.num_days() > MAX_TTM_SPAN_DAYS {
+            return None;
+        }
+        let quarters: Vec<&T> = last_four.iter().map(|(_, q)| *q).collect();
+        Some(T::sum_quarters(&quarters))
+    }
+}
+
+/// Four consecutive quarter ends are roughly
+/// 273 days apart, anything longer has gaps
+const MAX_TTM_SPAN_DAYS: i64 = 300;
+
+fn series<T>(periods: &BTreeMap<String, T>) -> Vec<(NaiveDate, &T)> {
+    let mut series: Vec<(NaiveDate, &T)> = periods
+        .iter()
+        .filter_map(|(date, period)| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .map(|date| (date, period))
+        })
+        .collect();
+    series.sort_by_key(|(date, _)| *date);
+    series
+}
+
+/// Statements covering a period (instead of a point in time)
+/// whose quarters can be summed up to a TTM value
+pub trait EODHDFlowStatement: Sized {
+    fn sum_quarters(quarters: &[&Self]) -> Self;
+}
+
+fn sum<T>(quarters: &[&T], field: impl Fn(&T) -> Option<f64>) -> Option<f64> {
+    quarters.iter().map(|q| field(q)).sum()
+}
+
+/// Line items of `Financials::Income_Statement`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EODHDIncomeStatement {
+    #[serde(default, with = "eodhd_serde_opt_date")]
+    pub date: Option<NaiveDate>,
+    #[serde(rename = "filing_date", default, with = "eodhd_serde_opt_date")]
+    pub filing_date: Option<NaiveDate>,
+    #[serde(rename = "currency_symbol")]
+    pub currency_symbol: Option<String>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub research_development: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub effect_of_accounting_charges: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub income_before_tax: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub minority_interest: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_income: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub selling_general_administrative: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub selling_and_marketing_expenses: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub gross_profit: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub reconciled_depreciation: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub ebit: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub ebitda: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub depreciation_and_amortization: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub non_operating_income_net_other: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub operating_income: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_operating_expenses: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub interest_expense: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub tax_provision: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub interest_income: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_interest_income: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub extraordinary_items: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub non_recurring: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_items: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub income_tax_expense: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_operating_expenses: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub cost_of_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_other_income_expense_net: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub discontinued_operations: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_income_from_continuing_ops: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_income_applicable_to_common_shares: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub preferred_stock_and_other_adjustments: Option<f64>,
+    /// line items not covered by the fields above
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+/// Line items of `Financials::Balance_Sheet`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EODHDBalanceSheet {
+    #[serde(default, with = "eodhd_serde_opt_date")]
+    pub date: Option<NaiveDate>,
+    #[serde(rename = "filing_date", default, with = "eodhd_serde_opt_date")]
+    pub filing_date: Option<NaiveDate>,
+    #[serde(rename = "currency_symbol")]
+    pub currency_symbol: Option<String>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_assets: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub intangible_assets: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub earning_assets: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_current_assets: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_liab: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_stockholder_equity: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub deferred_long_term_liab: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_current_liab: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub common_stock: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub capital_stock: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub retained_earnings: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_liab: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub good_will: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_assets: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub cash: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub cash_and_equivalents: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_current_liabilities: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub current_deferred_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_debt: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub short_term_debt: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub short_long_term_debt: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub short_long_term_debt_total: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_stockholder_equity: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub property_plant_equipment: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_current_assets: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub long_term_investments: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_tangible_assets: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub short_term_investments: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_receivables: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub long_term_debt: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub inventory: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub accounts_payable: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_permanent_equity: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub noncontrolling_interest_in_consolidated_entity: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub temporary_equity_redeemable_noncontrolling_interests: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub accumulated_other_comprehensive_income: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub additional_paid_in_capital: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub common_stock_total_equity: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub preferred_stock_total_equity: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub retained_earnings_total_equity: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub treasury_stock: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub accumulated_amortization: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub deferred_long_term_asset_charges: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub non_current_assets_total: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub capital_lease_obligations: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub long_term_debt_total: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub non_current_liabilities_other: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub non_current_liabilities_total: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub negative_goodwill: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub warrants: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub preferred_stock_redeemable: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub capital_surpluse: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub liabilities_and_stockholders_equity: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub cash_and_short_term_investments: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub property_plant_and_equipment_gross: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub property_plant_and_equipment_net: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub accumulated_depreciation: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_working_capital: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_invested_capital: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub common_stock_shares_outstanding: Option<f64>,
+    /// line items not covered by the fields above
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+/// Line items of `Financials::Cash_Flow`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EODHDCashFlow {
+    #[serde(default, with = "eodhd_serde_opt_date")]
+    pub date: Option<NaiveDate>,
+    #[serde(rename = "filing_date", default, with = "eodhd_serde_opt_date")]
+    pub filing_date: Option<NaiveDate>,
+    #[serde(rename = "currency_symbol")]
+    pub currency_symbol: Option<String>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub investments: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_to_liabilities: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_cashflows_from_investing_activities: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_borrowings: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_cash_from_financing_activities: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_to_operating_activities: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub net_income: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_in_cash: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub begin_period_cash_flow: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub end_period_cash_flow: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub total_cash_from_operating_activities: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub issuance_of_capital_stock: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub depreciation: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_cashflows_from_investing_activities: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub dividends_paid: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_to_inventory: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_to_account_receivables: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub sale_purchase_of_stock: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_cashflows_from_financing_activities: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_to_netincome: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub capital_expenditures: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_receivables: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub cash_flows_other_operating: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub exchange_rate_changes: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub cash_and_cash_equivalents_changes: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_in_working_capital: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub stock_based_compensation: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub other_non_cash_items: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub free_cash_flow: Option<f64>,
+    /// line items not covered by the fields above
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+impl EODHDFlowStatement for EODHDIncomeStatement {
+    fn sum_quarters(quarters: &[&Self]) -> Self {
+        let latest = quarters.last();
+        Self {
+            date: latest.and_then(|q| q.date),
+            filing_date: latest.and_then(|q| q.filing_date),
+            currency_symbol: latest.and_then(|q| q.currency_symbol.clone()),
+            research_development: sum(quarters, |q| q.research_development),
+            effect_of_accounting_charges: sum(quarters, |q| q.effect_of_accounting_charges),
+            income_before_tax: sum(quarters, |q| q.income_before_tax),
+            minority_interest: sum(quarters, |q| q.minority_interest),
+            net_income: sum(quarters, |q| q.net_income),
+            selling_general_administrative: sum(quarters, |q| q.selling_general_administrative),
+            selling_and_marketing_expenses: sum(quarters, |q| q.selling_and_marketing_expenses),
+            gross_profit: sum(quarters, |q| q.gross_profit),
+            reconciled_depreciation: sum(quarters, |q| q.reconciled_depreciation),
+            ebit: sum(quarters, |q| q.ebit),
+            ebitda: sum(quarters, |q| q.ebitda),
+            depreciation_and_amortization: sum(quarters, |q| q.depreciation_and_amortization),
+            non_operating_income_net_other: sum(quarters, |q| q.non_operating_income_net_other),
+            operating_income: sum(quarters, |q| q.operating_income),
+            other_operating_expenses: sum(quarters, |q| q.other_operating_expenses),
+            interest_expense: sum(quarters, |q| q.interest_expense),
+            tax_provision: sum(quarters, |q| q.tax_provision),
+            interest_income: sum(quarters, |q| q.interest_income),
+            net_interest_income: sum(quarters, |q| q.net_interest_income),
+            extraordinary_items: sum(quarters, |q| q.extraordinary_items),
+            non_recurring: sum(quarters, |q| q.non_recurring),
+            other_items: sum(quarters, |q| q.other_items),
+            income_tax_expense: sum(quarters, |q| q.income_tax_expense),
+            total_revenue: sum(quarters, |q| q.total_revenue),
+            total_operating_expenses: sum(quarters, |q| q.total_operating_expenses),
+            cost_of_revenue: sum(quarters, |q| q.cost_of_revenue),
+            total_other_income_expense_net: sum(quarters, |q| q.total_other_income_expense_net),
+            discontinued_operations: sum(quarters, |q| q.discontinued_operations),
+            net_income_from_continuing_ops: sum(quarters, |q| q.net_income_from_continuing_ops),
+            net_income_applicable_to_common_shares: sum(quarters, |q| {
+                q.net_income_applicable_to_common_shares
+            }),
+            preferred_stock_and_other_adjustments: sum(quarters, |q| {
+                q.preferred_stock_and_other_adjustments
+            }),
+            other: BTreeMap::new(),
+        }
+    }
+}
+
+impl EODHDFlowStatement for EODHDCashFlow {
+    fn sum_quarters(quarters: &[&Self]) -> Self {
+        let latest = quarters.last();
+        Self {
+            date: latest.and_then(|q| q.date),
+            filing_date: latest.and_then(|q| q.filing_date),
+            currency_symbol: latest.and_then(|q| q.currency_symbol.clone()),
+            investments: sum(quarters, |q| q.investments),
+            change_to_liabilities: sum(quarters, |q| q.change_to_liabilities),
+            total_cashflows_from_investing_activities: sum(quarters, |q| {
+                q.total_cashflows_from_investing_activities
+            }),
+            net_borrowings: sum(quarters, |q| q.net_borrowings),
+            total_cash_from_financing_activities: sum(quarters, |q| {
+                q.total_cash_from_financing_activities
+            }),
+            change_to_operating_activities: sum(quarters, |q| q.change_to_operating_activities),
+            net_income: sum(quarters, |q| q.net_income),
+            change_in_cash: sum(quarters, |q| q.change_in_cash),
+            // balances at the start of the oldest and the end of the newest quarter
+            begin_period_cash_flow: quarters.first().and_then(|q| q.begin_period_cash_flow),
+            end_period_cash_flow: latest.and_then(|q| q.end_period_cash_flow),
+            total_cash_from_operating_activities: sum(quarters, |q| {
+                q.total_cash_from_operating_activities
+            }),
+            issuance_of_capital_stock: sum(quarters, |q| q.issuance_of_capital_stock),
+            depreciation: sum(quarters, |q| q.depreciation),
+            other_cashflows_from_investing_activities: sum(quarters, |q| {
+                q.other_cashflows_from_investing_activities
+            }),
+            dividends_paid: sum(quarters, |q| q.dividends_paid),
+            change_to_inventory: sum(quarters, |q| q.change_to_inventory),
+            change_to_account_receivables: sum(quarters, |q| q.change_to_account_receivables),
+            sale_purchase_of_stock: sum(quarters, |q| q.sale_purchase_of_stock),
+            other_cashflows_from_financing_activities: sum(quarters, |q| {
+                q.other_cashflows_from_financing_activities
+            }),
+            change_to_netincome: sum(quarters, |q| q.change_to_netincome),
+            capital_expenditures: sum(quarters, |q| q.capital_expenditures),
+            change_receivables: sum(quarters, |q| q.change_receivables),
+            cash_flows_other_operating: sum(quarters, |q| q.cash_flows_other_operating),
+            exchange_rate_changes: sum(quarters, |q| q.exchange_rate_changes),
+            cash_and_cash_equivalents_changes: sum(quarters, |q| {
+                q.cash_and_cash_equivalents_changes
+            }),
+            change_in_working_capital: sum(quarters, |q| q.change_in_working_capital),
+            stock_based_compensation: sum(quarters, |q| q.stock_based_compensation),
+            other_non_cash_items: sum(quarters, |q| q.other_non_cash_items),
+            free_cash_flow: sum(quarters, |q| q.free_cash_flow),
+            other: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cash_flow(date: &str, begin: f64, end: f64, net_income: f64) -> EODHDCashFlow {
+        EODHDCashFlow {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").ok(),
+            begin_period_cash_flow: Some(begin),
+            end_period_cash_flow: Some(end),
+            net_income: Some(net_income),
+            ..Default::default()
+        }
+    }
+
+    fn cash_flows(quarters: &[(&str, f64, f64, f64)]) -> EODHDStatement<EODHDCashFlow> {
+        EODHDStatement {
+            quarterly: quarters
+                .iter()
+                .map(|(date, begin, end, net_income)| {
+                    (date.to_string(), cash_flow(date, *begin, *end, *net_income))
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ttm_sums_flows_and_keeps_balances_at_the_window_edges() {
+        let statement = cash_flows(&[
+            ("2022-09-30", 0.0, 10.0, 100.0),
+            ("2022-12-31", 10.0, 20.0, 1.0),
+            ("2023-03-31", 20.0, 30.0, 2.0),
+            ("2023-06-30", 30.0, 40.0, 3.0),
+            ("2023-09-30", 40.0, 50.0, 4.0),
+        ]);
+        let ttm = statement.ttm().unwrap();
+        assert_eq!(ttm.net_income, Some(10.0));
+        assert_eq!(ttm.begin_period_cash_flow, Some(10.0));
+        assert_eq!(ttm.end_period_cash_flow, Some(50.0));
+        assert_eq!(ttm.date, NaiveDate::from_ymd_opt(2023, 9, 30));
+    }
+
+    #[test]
+    fn ttm_line_item_missing_in_a_quarter_is_none() {
+        let mut statement = cash_flows(&[
+            ("2022-12-31", 10.0, 20.0, 1.0),
+            ("2023-03-31", 20.0, 30.0, 2.0),
+            ("2023-06-30", 30.0, 40.0, 3.0),
+            ("2023-09-30", 40.0, 50.0, 4.0),
+        ]);
+        statement
+            .quarterly
+            .get_mut("2023-03-31")
+            .unwrap()
+            .net_income = None;
+        assert_eq!(statement.ttm().unwrap().net_income, None);
+    }
+
+    #[test]
+    fn ttm_needs_four_consecutive_quarters() {
+        let statement = cash_flows(&[
+            ("2023-03-31", 20.0, 30.0, 2.0),
+            ("2023-06-30", 30.0, 40.0, 3.0),
+            ("2023-09-30", 40.0, 50.0, 4.0),
+        ]);
+        assert!(statement.ttm().is_none());
+
+        let statement = cash_flows(&[
+            ("2021-12-31", 10.0, 20.0, 1.0),
+            ("2023-03-31", 20.0, 30.0, 2.0),
+            ("2023-06-30", 30.0, 40.0, 3.0),
+            ("2023-09-30", 40.0, 50.0, 4.0),
+        ]);
+        assert!(statement.ttm().is_none());
+    }
+
+    #[test]
+    fn series_are_sorted_and_skip_malformed_dates() {
+        let mut statement = EODHDStatement::<EODHDBalanceSheet>::default();
+        for (date, total_assets) in [("2023-06-30", 2.0), ("2022-12-31", 1.0), ("latest", 9.0)] {
+            statement.quarterly.insert(
+                date.to_string(),
+                EODHDBalanceSheet {
+                    total_assets: Some(total_assets),
+                    ..Default::default()
+                },
+            );
+        }
+        statement.yearly.insert(
+            "2022-12-31".to_string(),
+            EODHDBalanceSheet {
+                total_assets: Some(1.0),
+                ..Default::default()
+            },
+        );
+
+        let quarterly = statement.quarterly_series();
+        let dates = quarterly.iter().map(|(date, _)| *date).collect::<Vec<_>>();
+        assert_eq!(
+            dates,
+            vec![
+                NaiveDate::from_ymd_opt(2022, 12, 31).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 6, 30).unwrap()
+            ]
+        );
+        // balances are points in time, taken as reported
+        assert_eq!(quarterly[1].1.total_assets, Some(2.0));
+        assert_eq!(statement.yearly_series().len(), 1);
+    }
+}
@@ -0,0 +1,360 @@
+use super::{eodhd_indexed_vec, EODHDGeneral, EODHDTechnicals};
+use crate::{datetime::eodhd_serde_opt_date, eodhd_opt_string_float, eodhd_opt_string_int};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Fundamentals of an ETF
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDETFFundamentals {
+    #[serde(rename = "General", default)]
+    pub general: Option<EODHDGeneral>,
+    #[serde(rename = "Technicals", default)]
+    pub technicals: Option<EODHDTechnicals>,
+    #[serde(rename = "ETF_Data", default)]
+    pub etf_data: Option<EODHDETFData>,
+}
+
+/// EODHD delivers almost all numbers
+/// of this section as strings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDETFData {
+    #[serde(rename = "ISIN")]
+    pub isin: Option<String>,
+    #[serde(rename = "Company_Name")]
+    pub company_name: Option<String>,
+    #[serde(rename = "Company_URL")]
+    pub company_url: Option<String>,
+    #[serde(rename = "ETF_URL")]
+    pub etf_url: Option<String>,
+    #[serde(rename = "Domicile")]
+    pub domicile: Option<String>,
+    #[serde(rename = "Index_Name")]
+    pub index_name: Option<String>,
+    #[serde(
+        rename = "Yield",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub yield_p: Option<f64>,
+    #[serde(rename = "Dividend_Paying_Frequency")]
+    pub dividend_paying_frequency: Option<String>,
+    #[serde(rename = "Inception_Date", default, with = "eodhd_serde_opt_date")]
+    pub inception_date: Option<NaiveDate>,
+    #[serde(
+        rename = "Max_Annual_Mgmt_Charge",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub max_annual_mgmt_charge: Option<f64>,
+    #[serde(
+        rename = "Ongoing_Charge",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub ongoing_charge: Option<f64>,
+    #[serde(rename = "Date_Ongoing_Charge", default, with = "eodhd_serde_opt_date")]
+    pub date_ongoing_charge: Option<NaiveDate>,
+    /// expense ratio
+    #[serde(
+        rename = "NetExpenseRatio",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub net_expense_ratio: Option<f64>,
+    #[serde(
+        rename = "AnnualHoldingsTurnover",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub annual_holdings_turnover: Option<f64>,
+    #[serde(
+        rename = "TotalAssets",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub total_assets: Option<f64>,
+    #[serde(
+        rename = "Average_Mkt_Cap_Mil",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub average_mkt_cap_mil: Option<f64>,
+    #[serde(rename = "Holdings_Count", default, with = "eodhd_opt_string_int")]
+    pub holdings_count: Option<u64>,
+    /// keyed by asset class, e.g. "Stock US", "Bond", "Cash"
+    #[serde(rename = "Asset_Allocation", default)]
+    pub asset_allocation: BTreeMap<String, EODHDAssetAllocation>,
+    /// keyed by region, e.g. "North America"
+    #[serde(rename = "World_Regions", default)]
+    pub world_regions: BTreeMap<String, EODHDWeight>,
+    /// keyed by sector, e.g. "Technology"
+    #[serde(rename = "Sector_Weights", default)]
+    pub sector_weights: BTreeMap<String, EODHDWeight>,
+    /// keyed by ticker, e.g. "AAPL.US"
+    #[serde(rename = "Top_10_Holdings", default)]
+    pub top_10_holdings: BTreeMap<String, EODHDETFHolding>,
+    /// keyed by ticker, e.g. "AAPL.US"
+    #[serde(rename = "Holdings", default)]
+    pub holdings: BTreeMap<String, EODHDETFHolding>,
+    #[serde(rename = "Performance", default)]
+    pub performance: Option<EODHDETFPerformance>,
+    /// sections not covered by the fields above
+    /// (Market_Capitalisation, Fixed_Income, MorningStar...)
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDAssetAllocation {
+    #[serde(
+        rename = "Long_%",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub long_p: Option<f64>,
+    #[serde(
+        rename = "Short_%",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub short_p: Option<f64>,
+    #[serde(
+        rename = "Net_Assets_%",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub net_assets_p: Option<f64>,
+}
+
+/// Share of a region/sector in percent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDWeight {
+    #[serde(
+        rename = "Equity_%",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub equity_p: Option<f64>,
+    #[serde(
+        rename = "Relative_to_Category",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub relative_to_category: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDETFHolding {
+    #[serde(rename = "Code")]
+    pub code: Option<String>,
+    #[serde(rename = "Exchange")]
+    pub exchange: Option<String>,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "Sector")]
+    pub sector: Option<String>,
+    #[serde(rename = "Industry")]
+    pub industry: Option<String>,
+    #[serde(rename = "Country")]
+    pub country: Option<String>,
+    #[serde(rename = "Region")]
+    pub region: Option<String>,
+    /// share of the ETF's assets in percent
+    #[serde(
+        rename = "Assets_%",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub assets_p: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDETFPerformance {
+    #[serde(
+        rename = "1y_Volatility",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub volatility_1y: Option<f64>,
+    #[serde(
+        rename = "3y_Volatility",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub volatility_3y: Option<f64>,
+    #[serde(
+        rename = "3y_ExpReturn",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub expected_return_3y: Option<f64>,
+    #[serde(
+        rename = "3y_SharpRatio",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub sharpe_ratio_3y: Option<f64>,
+    #[serde(
+        rename = "Returns_YTD",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub returns_ytd: Option<f64>,
+    #[serde(
+        rename = "Returns_1Y",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub returns_1y: Option<f64>,
+    #[serde(
+        rename = "Returns_3Y",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub returns_3y: Option<f64>,
+    #[serde(
+        rename = "Returns_5Y",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub returns_5y: Option<f64>,
+    #[serde(
+        rename = "Returns_10Y",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub returns_10y: Option<f64>,
+}
+
+/// Fundamentals of a mutual fund
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDFundFundamentals {
+    #[serde(rename = "General", default)]
+    pub general: Option<EODHDGeneral>,
+    #[serde(rename = "MutualFund_Data", default)]
+    pub mutual_fund_data: Option<EODHDMutualFundData>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDMutualFundData {
+    #[serde(rename = "Fund_Category")]
+    pub fund_category: Option<String>,
+    #[serde(rename = "Fund_Style")]
+    pub fund_style: Option<String>,
+    #[serde(
+        rename = "Nav",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub nav: Option<f64>,
+    #[serde(
+        rename = "Prev_Close_Price",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub prev_close_price: Option<f64>,
+    #[serde(rename = "Update_Date", default, with = "eodhd_serde_opt_date")]
+    pub update_date: Option<NaiveDate>,
+    #[serde(
+        rename = "Portfolio_Net_Assets",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub portfolio_net_assets: Option<f64>,
+    #[serde(
+        rename = "Share_Class_Net_Assets",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub share_class_net_assets: Option<f64>,
+    #[serde(
+        rename = "Morning_Star_Rating",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub morning_star_rating: Option<f64>,
+    #[serde(
+        rename = "Morning_Star_Risk_Rating",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub morning_star_risk_rating: Option<f64>,
+    #[serde(rename = "Morning_Star_Category")]
+    pub morning_star_category: Option<String>,
+    #[serde(rename = "Inception_Date", default, with = "eodhd_serde_opt_date")]
+    pub inception_date: Option<NaiveDate>,
+    #[serde(rename = "Currency")]
+    pub currency: Option<String>,
+    #[serde(rename = "Domicile")]
+    pub domicile: Option<String>,
+    #[serde(
+        rename = "Yield",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub yield_p: Option<f64>,
+    #[serde(
+        rename = "Yield_YTD",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub yield_ytd: Option<f64>,
+    #[serde(
+        rename = "Expense_Ratio",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub expense_ratio: Option<f64>,
+    #[serde(rename = "Expense_Ratio_Date", default, with = "eodhd_serde_opt_date")]
+    pub expense_ratio_date: Option<NaiveDate>,
+    /// sections not covered by the fields above
+    /// (Asset_Allocation, Top_Holdings, Sector_Weights...)
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+/// Fundamentals of an index (e.g. `GSPC.INDX`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDIndexFundamentals {
+    #[serde(rename = "General", default)]
+    pub general: Option<EODHDGeneral>,
+    #[serde(rename = "Components", default, with = "eodhd_indexed_vec")]
+    pub components: Vec<EODHDIndexComponent>,
+    #[serde(
+        rename = "HistoricalTickerComponents",
+        default,
+        with = "eodhd_indexed_vec"
+    )]
+    pub historical_components: Vec<EODHDHistoricalIndexComponent>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDIndexComponent {
+    #[serde(rename = "Code")]
+    pub code: Option<String>,
+    #[serde(rename = "Exchange")]
+    pub exchange: Option<String>,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "Sector")]
+    pub sector: Option<String>,
+    #[serde(rename = "Industry")]
+    pub industry: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EODHDHistoricalIndexComponent {
+    #[serde(rename = "Code")]
+    pub code: Option<String>,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "StartDate", default, with = "eodhd_serde_opt_date")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(rename = "EndDate", default, with = "eodhd_serde_opt_date")]
+    pub end_date: Option<NaiveDate>,
+    #[serde(rename = "IsActiveNow", default, with = "eodhd_opt_string_int")]
+    pub is_active_now: Option<u8>,
+    #[serde(rename = "IsDelisted", default, with = "eodhd_opt_string_int")]
+    pub is_delisted: Option<u8>,
+}
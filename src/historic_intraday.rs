@@ -1,27 +1,36 @@
-use super::{datetime::EODHDInterval, env_eodhd_token, BASE_URL};
+use super::{
+    datetime::{eodhd_timestamp, EODHDInterval, EODHDTimestamped},
+    eodhd_opt_string_float, eodhd_opt_string_int, eodhd_string_int,
+    rate_limit::EODHDEndpoint,
+    ticker::{parse_ticker, EODHDTicker},
+    EODHDClient, EODHDError,
+};
+use chrono::{DateTime, FixedOffset, Utc};
+use futures_util::{stream, StreamExt, TryStreamExt};
 use serde::Deserialize;
-use std::error::Error;
 
 /// e.g.
 /// Timestamp,Gmtoffset,Datetime,Open,High,Low,Close,Volume
 /// 1647504300,0,"2022-03-17 08:05:00",16.86,16.868,16.775999,16.802,129473
 #[derive(Clone, Debug, Deserialize)]
 pub struct EODHDHistoricIntraday {
-    #[serde(rename = "timestamp", default)]
+    /// unix timestamp in seconds, 0 if EODHD didn't deliver one
+    #[serde(rename = "timestamp", default, with = "eodhd_string_int")]
     pub t: i64,
-    #[serde(rename = "gmtoffset", default)]
-    pub gmt: Option<u8>,
+    /// offset of the exchange to UTC in seconds
+    #[serde(rename = "gmtoffset", default, with = "eodhd_opt_string_int")]
+    pub gmt: Option<i32>,
     #[serde(rename = "datetime", default)]
     pub dt: String,
-    #[serde(rename = "open", default)]
+    #[serde(rename = "open", default, with = "eodhd_opt_string_float")]
     pub o: Option<f64>,
-    #[serde(rename = "high", default)]
+    #[serde(rename = "high", default, with = "eodhd_opt_string_float")]
     pub h: Option<f64>,
-    #[serde(rename = "low", default)]
+    #[serde(rename = "low", default, with = "eodhd_opt_string_float")]
     pub l: Option<f64>,
-    #[serde(rename = "close", default)]
+    #[serde(rename = "close", default, with = "eodhd_opt_string_float")]
     pub c: Option<f64>,
-    #[serde(rename = "volume", default)]
+    #[serde(rename = "volume", default, with = "eodhd_opt_string_int")]
     pub v: Option<i64>,
 }
 
@@ -40,44 +49,277 @@ impl Default for EODHDHistoricIntraday {
     }
 }
 
+impl EODHDTimestamped for EODHDHistoricIntraday {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        if self.t <= 0 {
+            return None;
+        }
+        eodhd_timestamp(self.t)
+    }
+}
+
+impl EODHDTimestamped for EODHDIntradayBar {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        Some(self.time)
+    }
+}
+
 pub struct HistoricIntradayOptions {
+    /// unix timestamp in seconds
     pub from: Option<i64>,
+    /// unix timestamp in seconds, now if not provided
     pub to: Option<i64>,
     pub interval: EODHDInterval,
+    /// requests sent at once when the range needs several,
+    /// 0 and 1 fetch one after the other
+    pub concurrency: usize,
+}
+
+impl EODHDClient {
+    /// Intraday bars from `options.from` to `options.to`, ordered
+    /// by timestamp. Ranges longer than EODHD answers at once
+    /// (see [EODHDInterval::max_request_range]) are split into
+    /// several requests, each costs api calls on its own.
+    /// Without `from` EODHD's default range is returned.
+    pub async fn get_historic_intraday(
+        &self,
+        ticker: &EODHDTicker,
+        options: HistoricIntradayOptions,
+    ) -> Result<Vec<EODHDHistoricIntraday>, EODHDError> {
+        let from = match options.from {
+            Some(from) => from,
+            None => {
+                return self
+                    .get_historic_intraday_range(ticker, options.interval, None, options.to)
+                    .await
+            }
+        };
+        let to = options.to.unwrap_or_else(|| chrono::Utc::now().timestamp());
+        let windows = request_windows(from, to, options.interval.max_request_range());
+
+        let chunks: Vec<Vec<EODHDHistoricIntraday>> = stream::iter(windows)
+            .map(|(start, end)| {
+                self.get_historic_intraday_range(ticker, options.interval, Some(start), Some(end))
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        // in case EODHD answers with bars outside the window
+        let mut bars = chunks.into_iter().flatten().collect::<Vec<_>>();
+        bars.sort_by_key(|bar| bar.t);
+        bars.dedup_by_key(|bar| bar.t);
+        Ok(bars)
+    }
+
+    async fn get_historic_intraday_range(
+        &self,
+        ticker: &EODHDTicker,
+        interval: EODHDInterval,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> Result<Vec<EODHDHistoricIntraday>, EODHDError> {
+        let mut query = vec![("interval", interval.to_string())];
+        if let Some(from) = from {
+            query.push(("from", from.to_string()));
+        }
+        if let Some(to) = to {
+            query.push(("to", to.to_string()));
+        }
+
+        self.get_ticker_json(
+            EODHDEndpoint::HistoricIntraday,
+            ticker,
+            &format!("intraday/{}", ticker.path_segment()),
+            &query,
+        )
+        .await
+    }
+}
+
+/// Splits `from..=to` into windows of at most `max` seconds
+/// (`end - start <= max`), both ends inclusive like EODHD's
+/// `from` and `to`, each starting a second after the previous
+/// one ended. Empty if `from` is after `to`.
+fn request_windows(from: i64, to: i64, max: i64) -> Vec<(i64, i64)> {
+    let max = max.max(1);
+    let mut windows = vec![];
+    let mut start = from;
+    while start <= to {
+        let end = start.saturating_add(max).min(to);
+        windows.push((start, end));
+        if end == i64::MAX {
+            break;
+        }
+        start = end + 1;
+    }
+    windows
+}
+
+/// What to do with bars lacking a price or the volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDIncompleteBars {
+    /// leave them out of the series
+    Drop,
+    /// keep them with `complete` set to false, missing
+    /// prices are `f64::NAN` and a missing volume 0
+    Flag,
+}
+
+/// An [EODHDHistoricIntraday] with typed timestamps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EODHDIntradayBar {
+    /// start of the bar
+    pub time: DateTime<Utc>,
+    /// start of the bar in the exchange's time
+    /// (at the time of the bar)
+    pub local: DateTime<FixedOffset>,
+    pub o: f64,
+    pub h: f64,
+    pub l: f64,
+    pub c: f64,
+    pub v: i64,
+    /// all prices and the volume were delivered
+    pub complete: bool,
+}
+
+impl EODHDIntradayBar {
+    /// `None` if the bar has no valid timestamp or offset
+    pub fn from_raw(raw: &EODHDHistoricIntraday) -> Option<Self> {
+        let time = raw.time()?;
+        let offset = FixedOffset::east_opt(raw.gmt.unwrap_or(0))?;
+        let price = |p: Option<f64>| p.unwrap_or(f64::NAN);
+        Some(Self {
+            time,
+            local: time.with_timezone(&offset),
+            o: price(raw.o),
+            h: price(raw.h),
+            l: price(raw.l),
+            c: price(raw.c),
+            v: raw.v.unwrap_or(0),
+            complete: raw.o.is_some()
+                && raw.h.is_some()
+                && raw.l.is_some()
+                && raw.c.is_some()
+                && raw.v.is_some(),
+        })
+    }
 }
 
+impl EODHDClient {
+    /// [EODHDClient::get_historic_intraday] as typed bars,
+    /// bars without a valid timestamp are always dropped.
+    pub async fn get_intraday_bars(
+        &self,
+        ticker: &EODHDTicker,
+        options: HistoricIntradayOptions,
+        incomplete: EODHDIncompleteBars,
+    ) -> Result<Vec<EODHDIntradayBar>, EODHDError> {
+        Ok(self
+            .get_historic_intraday(ticker, options)
+            .await?
+            .iter()
+            .filter_map(EODHDIntradayBar::from_raw)
+            .filter(|bar| bar.complete || incomplete == EODHDIncompleteBars::Flag)
+            .collect())
+    }
+}
+
+/// Shorthand for [EODHDClient::get_historic_intraday]
+/// with a client created from `EODHD_TOKEN`.
 pub async fn get_historic_intraday(
     ticker: &str,
     options: HistoricIntradayOptions,
-) -> Result<Vec<EODHDHistoricIntraday>, Box<dyn Error>> {
-    let token = env_eodhd_token();
-    let mut url = format!(
-        "{base_url}/intraday/{ticker}?api_token={token}&fmt=json&interval={interval}",
-        base_url = BASE_URL,
-        ticker = ticker,
-        token = token,
-        interval = options.interval.to_string()
-    );
-
-    if let Some(from) = options.from {
-        url = format!("{url}&from={from}", url = url, from = from);
-    }
-    if let Some(to) = options.to {
-        url = format!("{url}&to={to}", url = url, to = to);
+) -> Result<Vec<EODHDHistoricIntraday>, EODHDError> {
+    EODHDClient::from_env()?
+        .get_historic_intraday(&parse_ticker(ticker)?, options)
+        .await
+}
+
+/// Shorthand for [EODHDClient::get_intraday_bars]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_intraday_bars(
+    ticker: &str,
+    options: HistoricIntradayOptions,
+    incomplete: EODHDIncompleteBars,
+) -> Result<Vec<EODHDIntradayBar>, EODHDError> {
+    EODHDClient::from_env()?
+        .get_intraday_bars(&parse_ticker(ticker)?, options, incomplete)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(json: &str) -> EODHDHistoricIntraday {
+        serde_json::from_str(json).unwrap()
     }
 
-    let request = reqwest::get(url.clone()).await;
-    match request {
-        Ok(request) => match request.json::<Vec<EODHDHistoricIntraday>>().await {
-            Ok(pre_eodhd_ticks) => Ok(pre_eodhd_ticks),
-            Err(e) => {
-                log::error!("UNABLE TO PARSE eodhd RESPONSE {:?}", e);
-                Err(Box::new(e))
-            }
-        },
-        Err(e) => {
-            log::error!("REQUEST TO EODHD FAILED \n{:?}\n with {:?}", url, e);
-            Err(Box::new(e))
+    /// every second of `from..=to` is in exactly one window
+    fn assert_covers(windows: &[(i64, i64)], from: i64, to: i64, max: i64) {
+        assert_eq!(windows.first().unwrap().0, from);
+        assert_eq!(windows.last().unwrap().1, to);
+        for (start, end) in windows {
+            assert!(start <= end && end - start <= max);
         }
+        for pair in windows.windows(2) {
+            assert_eq!(pair[0].1 + 1, pair[1].0);
+        }
+    }
+
+    #[test]
+    fn request_windows_cover_the_range() {
+        let windows = request_windows(0, 250, 100);
+        assert_eq!(windows, vec![(0, 100), (101, 201), (202, 250)]);
+        assert_covers(&windows, 0, 250, 100);
+
+        // the last window is a single second
+        let windows = request_windows(0, 202, 100);
+        assert_eq!(windows.last(), Some(&(202, 202)));
+        assert_covers(&windows, 0, 202, 100);
+
+        // fits exactly
+        assert_eq!(request_windows(10, 110, 100), vec![(10, 110)]);
+
+        let max = EODHDInterval::Minute.max_request_range();
+        let (from, to) = (1_600_000_000, 1_700_000_000);
+        assert_covers(&request_windows(from, to, max), from, to, max);
+    }
+
+    #[test]
+    fn request_windows_edge_cases() {
+        assert_eq!(request_windows(5, 5, 100), vec![(5, 5)]);
+        assert_eq!(request_windows(6, 5, 100), vec![]);
+        assert_eq!(request_windows(0, 2, 0), vec![(0, 1), (2, 2)]);
+        assert_eq!(
+            request_windows(i64::MAX - 1, i64::MAX, 100),
+            vec![(i64::MAX - 1, i64::MAX)]
+        );
+    }
+
+    #[test]
+    fn bars_without_timestamp_are_dropped() {
+        let missing = raw(r#"{"gmtoffset":0,"open":1,"high":1,"low":1,"close":1,"volume":5}"#);
+        assert_eq!(missing.t, 0);
+        assert_eq!(missing.time(), None);
+        assert_eq!(EODHDIntradayBar::from_raw(&missing), None);
+
+        let bar = EODHDIntradayBar::from_raw(&raw(
+            r#"{"timestamp":"1647504300","gmtoffset":3600,"open":1,"high":2,"low":0.5,"close":1.5,"volume":5}"#,
+        ))
+        .unwrap();
+        assert_eq!(bar.time.timestamp(), 1647504300);
+        assert_eq!(bar.local.offset().local_minus_utc(), 3600);
+        assert!(bar.complete);
+    }
+
+    #[test]
+    fn bars_without_price_are_incomplete() {
+        let bar =
+            EODHDIntradayBar::from_raw(&raw(r#"{"timestamp":1647504300,"close":"NA"}"#)).unwrap();
+        assert!(!bar.complete);
+        assert!(bar.c.is_nan());
+        assert_eq!(bar.v, 0);
     }
 }
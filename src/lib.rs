@@ -1,63 +1,355 @@
-use std::{error::Error, fmt::Display};
-
 /// Based on [eodhd docs](
 /// https://eodhistoricaldata.com/financial-apis/category/alternative-data-financial-api/)
 pub mod alternative;
+/// The [EODHDClient] all endpoints are called on
+pub mod client;
 /// Helpful wrappers for serde to
 /// handle eodhd DateTime Formats
 pub mod datetime;
 /// Based on [eodhd
 /// docs](https://eodhistoricaldata.com/financial-apis/api-for-historical-data-and-volumes/)
 pub mod end_of_period;
+/// The [EODHDError] returned by all functions
+pub mod error;
+/// Based on [eodhd
+/// docs](https://eodhistoricaldata.com/financial-apis/exchanges-api-trading-hours-and-holidays/)
+pub mod exchanges;
 /// Based on [eodhd
 /// docs](https://eodhistoricaldata.com/financial-apis/stock-etfs-fundamental-data-feeds/)
 pub mod fundamentals;
 /// Based on [eodhd
 /// docs](https://eodhistoricaldata.com/financial-apis/intraday-historical-data-api/)
 pub mod historic_intraday;
+/// Client side limiting of requests
+/// per minute and api calls per day
+pub mod rate_limit;
 /// Based on [eodhd
 /// docs](https://eodhistoricaldata.com/financial-apis/live-realtime-stocks-api/)
 pub mod realtime;
+/// Aggregating bars to longer
+/// intervals/periods locally
+pub mod resample;
+/// Retrying failed requests with exponential backoff
+pub mod retry;
+/// Based on [eodhd
+/// docs](https://eodhistoricaldata.com/financial-apis/search-api-for-stocks-etfs-mutual-funds/)
+pub mod search;
+/// Based on [eodhd
+/// docs](https://eodhistoricaldata.com/financial-apis/api-splits-dividends/)
+pub mod splits_dividends;
+/// The [ticker::EODHDTicker] all endpoints
+/// for single instruments take
+pub mod ticker;
 
-fn env_eodhd_token() -> String {
-    if std::env::var("EODHD_TOKEN").is_err() {
-        panic!("Please add EODHD_TOKEN for authentication to env");
-    }
-    std::env::var("EODHD_TOKEN").unwrap()
+pub use client::EODHDClient;
+pub use error::EODHDError;
+
+fn env_eodhd_token() -> Result<String, EODHDError> {
+    std::env::var("EODHD_TOKEN").map_err(|_| EODHDError::MissingToken)
 }
 
 pub const BASE_URL: &str = "https://eodhistoricaldata.com/api";
 
-#[derive(Debug)]
-pub struct EODHDError {
-    pub description: String,
-    pub inner_error: Box<dyn Error + Send + Sync>,
-}
+/// Lenient number parsing shared by the
+/// `eodhd_*string_*` serde modules. EODHD encodes
+/// numbers as json numbers, numeric strings or
+/// placeholders (`null`, `""`, `"NA"`) in the same field.
+mod lenient {
+    use serde::{de, Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Lenient {
+        Int(i64),
+        Float(f64),
+        String(String),
+    }
+
+    /// EODHD's placeholders for values it doesn't have
+    fn is_missing(s: &str) -> bool {
+        let s = s.trim();
+        s.is_empty()
+            || s.eq_ignore_ascii_case("NA")
+            || s.eq_ignore_ascii_case("N/A")
+            || s.eq_ignore_ascii_case("null")
+    }
+
+    pub fn float<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+        match Option::<Lenient>::deserialize(deserializer)? {
+            Some(Lenient::Int(n)) => Ok(Some(n as f64)),
+            Some(Lenient::Float(f)) => Ok(Some(f)),
+            Some(Lenient::String(s)) if is_missing(&s) => Ok(None),
+            Some(Lenient::String(s)) => s
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| de::Error::custom(format!("expected a number, got {:?}", s))),
+            None => Ok(None),
+        }
+    }
 
-impl Display for EODHDError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.description.as_str())
+    pub fn int<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<i64>,
+    {
+        let integral = |f: f64| {
+            if f.fract() == 0.0 && f.abs() < i64::MAX as f64 {
+                Ok(f as i64)
+            } else {
+                Err(de::Error::custom(format!("expected an integer, got {}", f)))
+            }
+        };
+        let n = match Option::<Lenient>::deserialize(deserializer)? {
+            Some(Lenient::Int(n)) => n,
+            Some(Lenient::Float(f)) => integral(f)?,
+            Some(Lenient::String(s)) if is_missing(&s) => return Ok(None),
+            Some(Lenient::String(s)) => match s.trim().parse::<i64>() {
+                Ok(n) => n,
+                Err(_) => integral(s.trim().parse().map_err(|_| {
+                    de::Error::custom(format!("expected an integer, got {:?}", s))
+                })?)?,
+            },
+            None => return Ok(None),
+        };
+        T::try_from(n)
+            .map(Some)
+            .map_err(|_| de::Error::custom(format!("integer {} out of range", n)))
     }
 }
-impl Error for EODHDError {}
 
 /// EODHD sometimes encodes floats as strings
-/// in json responses.
+/// in json responses. Numbers and numeric strings
+/// are accepted, placeholders like `"NA"` become
+/// `f64::NAN` (use [eodhd_opt_string_float] where
+/// they are expected). Unlike [eodhd_string_int], which
+/// has no such value and fails on placeholders.
+/// Serialized as string, fields that are numbers in
+/// their serialized form only use it for parsing:
+/// `deserialize_with = "eodhd_string_float::deserialize"`.
 pub mod eodhd_string_float {
-    use serde::{self, Deserialize, Deserializer, Serializer};
+    use serde::{self, Deserializer, Serializer};
     pub fn serialize<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        return serializer.serialize_str(value.to_string().as_str());
+        serializer.serialize_str(value.to_string().as_str())
     }
 
     pub fn deserialize<'de, D>(deserializer: D) -> Result<f64, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        let value: Result<f64, D::Error> = s.parse().map_err(serde::de::Error::custom);
-        value
+        Ok(super::lenient::float(deserializer)?.unwrap_or(f64::NAN))
+    }
+}
+
+/// Like [eodhd_string_float] for optional values,
+/// accepts numbers, numeric strings, `null`, `""` and `"NA"`.
+pub mod eodhd_opt_string_float {
+    use serde::{self, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.serialize_str(value.to_string().as_str()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::lenient::float(deserializer)
+    }
+}
+
+/// Like [eodhd_string_float] for integers of any width,
+/// floats without fraction (e.g. `100.0`) are accepted.
+/// A placeholder like `"NA"` is an error (there is no
+/// integer counterpart of the float's `NaN`), use
+/// [eodhd_opt_string_int] where they are expected.
+pub mod eodhd_string_int {
+    use serde::{self, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        value.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<i64>,
+    {
+        super::lenient::int(deserializer)?
+            .ok_or_else(|| serde::de::Error::custom("expected an integer, got a placeholder"))
+    }
+}
+
+/// Like [eodhd_string_int] for optional values,
+/// accepts numbers, numeric strings, `null`, `""` and `"NA"`.
+pub mod eodhd_opt_string_int {
+    use serde::{self, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        value.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<i64>,
+    {
+        super::lenient::int(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Float {
+        #[serde(with = "crate::eodhd_string_float")]
+        value: f64,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct OptFloat {
+        #[serde(default, with = "crate::eodhd_opt_string_float")]
+        value: Option<f64>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Number {
+        #[serde(deserialize_with = "crate::eodhd_string_float::deserialize")]
+        value: f64,
+        #[serde(
+            default,
+            deserialize_with = "crate::eodhd_opt_string_float::deserialize"
+        )]
+        opt: Option<f64>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Int {
+        #[serde(with = "crate::eodhd_string_int")]
+        value: u32,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct OptInt {
+        #[serde(default, with = "crate::eodhd_opt_string_int")]
+        value: Option<i64>,
+    }
+
+    const PLACEHOLDERS: [&str; 5] = [r#""NA""#, r#""N/A""#, r#""""#, r#"" null ""#, "null"];
+
+    fn parse<T: DeserializeOwned>(value: &str) -> Result<T, serde_json::Error> {
+        serde_json::from_str(&format!(r#"{{"value": {}}}"#, value))
+    }
+
+    #[test]
+    fn string_float() {
+        assert_eq!(parse::<Float>("1.5").unwrap().value, 1.5);
+        assert_eq!(parse::<Float>("2").unwrap().value, 2.0);
+        assert_eq!(parse::<Float>(r#"" 1.5 ""#).unwrap().value, 1.5);
+        for placeholder in PLACEHOLDERS {
+            assert!(parse::<Float>(placeholder).unwrap().value.is_nan());
+        }
+        assert!(parse::<Float>(r#""abc""#).is_err());
+        assert_eq!(
+            serde_json::to_string(&Float { value: 1.5 }).unwrap(),
+            r#"{"value":"1.5"}"#
+        );
+        let nan = serde_json::to_string(&Float { value: f64::NAN }).unwrap();
+        assert!(serde_json::from_str::<Float>(&nan).unwrap().value.is_nan());
+    }
+
+    #[test]
+    fn opt_string_float() {
+        assert_eq!(parse::<OptFloat>("1.5").unwrap().value, Some(1.5));
+        assert_eq!(parse::<OptFloat>(r#""-3""#).unwrap().value, Some(-3.0));
+        for placeholder in PLACEHOLDERS {
+            assert_eq!(parse::<OptFloat>(placeholder).unwrap().value, None);
+        }
+        assert_eq!(serde_json::from_str::<OptFloat>("{}").unwrap().value, None);
+        assert!(parse::<OptFloat>(r#""abc""#).is_err());
+        assert_eq!(
+            serde_json::to_string(&OptFloat { value: Some(1.5) }).unwrap(),
+            r#"{"value":"1.5"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&OptFloat { value: None }).unwrap(),
+            r#"{"value":null}"#
+        );
+    }
+
+    #[test]
+    fn deserialize_only_keeps_numbers() {
+        let parsed: Number = serde_json::from_str(r#"{"value": "1.5", "opt": "2"}"#).unwrap();
+        assert_eq!((parsed.value, parsed.opt), (1.5, Some(2.0)));
+        let json = serde_json::to_string(&parsed).unwrap();
+        assert_eq!(json, r#"{"value":1.5,"opt":2.0}"#);
+        let again: Number = serde_json::from_str(&json).unwrap();
+        assert_eq!((again.value, again.opt), (1.5, Some(2.0)));
+
+        // serde_json writes NaN as null, which parses back to NaN
+        let nan = Number {
+            value: f64::NAN,
+            opt: None,
+        };
+        let json = serde_json::to_string(&nan).unwrap();
+        assert_eq!(json, r#"{"value":null,"opt":null}"#);
+        assert!(serde_json::from_str::<Number>(&json)
+            .unwrap()
+            .value
+            .is_nan());
+    }
+
+    #[test]
+    fn string_int() {
+        assert_eq!(parse::<Int>("7").unwrap().value, 7);
+        assert_eq!(parse::<Int>("7.0").unwrap().value, 7);
+        assert_eq!(parse::<Int>(r#""7""#).unwrap().value, 7);
+        assert_eq!(parse::<Int>(r#""7.0""#).unwrap().value, 7);
+        for placeholder in PLACEHOLDERS {
+            assert!(parse::<Int>(placeholder).is_err());
+        }
+        assert!(parse::<Int>("7.5").is_err());
+        assert!(parse::<Int>("-1").is_err());
+        assert_eq!(
+            serde_json::to_string(&Int { value: 7 }).unwrap(),
+            r#"{"value":7}"#
+        );
+    }
+
+    #[test]
+    fn opt_string_int() {
+        assert_eq!(parse::<OptInt>("-7").unwrap().value, Some(-7));
+        assert_eq!(
+            parse::<OptInt>(r#""1700000000""#).unwrap().value,
+            Some(1700000000)
+        );
+        for placeholder in PLACEHOLDERS {
+            assert_eq!(parse::<OptInt>(placeholder).unwrap().value, None);
+        }
+        assert_eq!(serde_json::from_str::<OptInt>("{}").unwrap().value, None);
+        assert!(parse::<OptInt>(r#""7.5""#).is_err());
+        assert_eq!(
+            serde_json::to_string(&OptInt { value: Some(7) }).unwrap(),
+            r#"{"value":7}"#
+        );
     }
 }
@@ -0,0 +1,225 @@
+use std::{
+    fmt::Display,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveDate, Utc};
+use log::debug;
+
+use crate::EODHDError;
+
+/// The endpoints of EODHD, needed to know
+/// how many api calls a request costs. [eodhd
+/// docs](https://eodhistoricaldata.com/financial-apis/api-limits/)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDEndpoint {
+    EndOfPeriod,
+    HistoricIntraday,
+    /// realtime delayed quotes, every ticker is one call
+    Delayed {
+        tickers: u32,
+    },
+    Sentiment,
+    Earnings,
+    EconomicEvents,
+    Fundamentals,
+    BulkFundamentals,
+    Dividends,
+    Splits,
+    /// 100 calls for a whole exchange, otherwise one per symbol
+    BulkEndOfPeriod {
+        symbols: u32,
+    },
+    Exchanges,
+    /// trading hours and holidays
+    ExchangeDetails,
+    SymbolList,
+    Search,
+    IdMapping,
+}
+
+impl EODHDEndpoint {
+    /// Amount of api calls subtracted
+    /// from the daily limit by one request.
+    pub fn cost(&self) -> u32 {
+        match self {
+            EODHDEndpoint::EndOfPeriod => 1,
+            EODHDEndpoint::HistoricIntraday => 5,
+            EODHDEndpoint::Delayed { tickers } => (*tickers).max(1),
+            EODHDEndpoint::Sentiment => 5,
+            EODHDEndpoint::Earnings => 1,
+            EODHDEndpoint::EconomicEvents => 1,
+            EODHDEndpoint::Fundamentals => 10,
+            EODHDEndpoint::BulkFundamentals => 100,
+            EODHDEndpoint::Dividends => 1,
+            EODHDEndpoint::Splits => 1,
+            EODHDEndpoint::BulkEndOfPeriod { symbols: 0 } => 100,
+            EODHDEndpoint::BulkEndOfPeriod { symbols } => *symbols,
+            EODHDEndpoint::Exchanges => 1,
+            EODHDEndpoint::ExchangeDetails => 5,
+            EODHDEndpoint::SymbolList => 1,
+            EODHDEndpoint::Search => 1,
+            EODHDEndpoint::IdMapping => 1,
+        }
+    }
+}
+
+/// What to do when there are no
+/// requests left for the current minute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDOnExhausted {
+    /// sleep until the bucket has refilled
+    Wait,
+    /// return [EODHDError::BudgetExhausted]
+    Error,
+}
+
+/// Limits of the subscribed plan
+#[derive(Debug, Clone, Copy)]
+pub struct EODHDRateLimit {
+    /// requests per minute
+    pub per_minute: u32,
+    /// api calls per day, weighted by [EODHDEndpoint::cost]
+    pub per_day: u32,
+    /// behaviour when `per_minute` is reached, running
+    /// out of daily calls always returns an error
+    pub on_exhausted: EODHDOnExhausted,
+}
+
+impl Default for EODHDRateLimit {
+    /// Limits of the paid plans
+    fn default() -> Self {
+        Self {
+            per_minute: 1000,
+            per_day: 100_000,
+            on_exhausted: EODHDOnExhausted::Wait,
+        }
+    }
+}
+
+/// Which limit is exhausted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDBudgetWindow {
+    Minute,
+    Day,
+}
+
+impl Display for EODHDBudgetWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EODHDBudgetWindow::Minute => "minute",
+            EODHDBudgetWindow::Day => "day",
+        })
+    }
+}
+
+/// Snapshot of the remaining budget
+#[derive(Debug, Clone, Copy)]
+pub struct EODHDRateBudget {
+    /// requests that can be sent right away
+    pub minute_remaining: u32,
+    /// api calls spent since midnight (UTC)
+    pub daily_used: u32,
+    /// api calls left until midnight (UTC)
+    pub daily_remaining: u32,
+}
+
+#[derive(Debug)]
+struct RateState {
+    tokens: f64,
+    last_refill: Instant,
+    day: NaiveDate,
+    daily_used: u32,
+}
+
+/// Token bucket for the per minute limit
+/// plus a counter for the daily api calls,
+/// shared by all clones of an [crate::EODHDClient].
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: EODHDRateLimit,
+    state: Mutex<RateState>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: EODHDRateLimit) -> Self {
+        Self {
+            limit,
+            state: Mutex::new(RateState {
+                tokens: limit.per_minute as f64,
+                last_refill: Instant::now(),
+                day: Utc::now().date_naive(),
+                daily_used: 0,
+            }),
+        }
+    }
+
+    /// A panic while the lock was held can't leave the
+    /// counters inconsistent, so poisoning is ignored
+    fn state(&self) -> MutexGuard<'_, RateState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn refill(&self, state: &mut RateState) {
+        let now = Instant::now();
+        let per_second = self.limit.per_minute as f64 / 60.0;
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * per_second).min(self.limit.per_minute as f64);
+        state.last_refill = now;
+
+        let today = Utc::now().date_naive();
+        if today != state.day {
+            state.day = today;
+            state.daily_used = 0;
+        }
+    }
+
+    /// Takes one request out of the bucket and `endpoint.cost()`
+    /// api calls out of the daily budget, waiting for the bucket
+    /// to refill if configured.
+    pub(crate) async fn acquire(&self, endpoint: EODHDEndpoint) -> Result<(), EODHDError> {
+        let cost = endpoint.cost();
+        loop {
+            let wait = {
+                let mut state = self.state();
+                self.refill(&mut state);
+
+                let daily_remaining = self.limit.per_day.saturating_sub(state.daily_used);
+                if cost > daily_remaining {
+                    return Err(EODHDError::BudgetExhausted {
+                        window: EODHDBudgetWindow::Day,
+                        cost,
+                        remaining: daily_remaining,
+                    });
+                }
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    state.daily_used += cost;
+                    return Ok(());
+                }
+                if self.limit.on_exhausted == EODHDOnExhausted::Error {
+                    return Err(EODHDError::BudgetExhausted {
+                        window: EODHDBudgetWindow::Minute,
+                        cost,
+                        remaining: 0,
+                    });
+                }
+                let per_second = self.limit.per_minute.max(1) as f64 / 60.0;
+                Duration::from_secs_f64((1.0 - state.tokens) / per_second)
+            };
+            debug!("rate limit reached, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    pub(crate) fn budget(&self) -> EODHDRateBudget {
+        let mut state = self.state();
+        self.refill(&mut state);
+        EODHDRateBudget {
+            minute_remaining: state.tokens.floor() as u32,
+            daily_used: state.daily_used,
+            daily_remaining: self.limit.per_day.saturating_sub(state.daily_used),
+        }
+    }
+}
@@ -1,13 +1,18 @@
-/// Setting up the socket and subscribing
-/// to ticks.
-pub mod socket;
+/// Building bars from socket ticks
+pub mod bars;
 /// Realtime data that is not sent
 /// over a socket [eodhd
 /// docs](https://eodhistoricaldata.com/financial-apis/live-realtime-stocks-api/)
 pub mod delayed;
 /// Also includes tick types for crypto
 pub mod forex;
-/// Tick types for quote & trades 
+/// Polling delayed quotes as a stream,
+/// for exchanges without a socket
+pub mod polling;
+/// Setting up the socket and subscribing
+/// to ticks.
+pub mod socket;
+/// Tick types for quote & trades
 pub mod us;
 
-const BASE_URL_SOCKET: &str = "wss://ws.eodhistoricaldata.com/ws";
+pub(crate) const BASE_URL_SOCKET: &str = "wss://ws.eodhistoricaldata.com/ws";
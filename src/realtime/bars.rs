@@ -0,0 +1,633 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use super::{
+    forex::{EODHDCryptoRT, EODHDForexRT},
+    socket::EODHDRTChannels,
+    us::{EODHDUSQuote, EODHDUSTrade},
+};
+use crate::datetime::{eodhd_timestamp_ms, EODHDTimestamped};
+
+/// A socket tick bars can be built from
+pub trait EODHDTick {
+    /// symbol as sent by the socket
+    fn symbol(&self) -> &str;
+    fn price(&self) -> f64;
+    /// traded quantity, quotes count as 1 (tick volume)
+    fn quantity(&self) -> f64;
+    /// timestamp in milliseconds
+    fn timestamp_ms(&self) -> i64;
+}
+
+/// Trades without a size add no volume
+impl EODHDTick for EODHDUSTrade {
+    fn symbol(&self) -> &str {
+        &self.s
+    }
+    fn price(&self) -> f64 {
+        self.p
+    }
+    fn quantity(&self) -> f64 {
+        self.v.unwrap_or(0.0)
+    }
+    fn timestamp_ms(&self) -> i64 {
+        self.t
+    }
+}
+
+/// Priced at the mid between bid and ask
+impl EODHDTick for EODHDUSQuote {
+    fn symbol(&self) -> &str {
+        &self.s
+    }
+    fn price(&self) -> f64 {
+        (self.ap + self.bp) / 2.0
+    }
+    fn quantity(&self) -> f64 {
+        1.0
+    }
+    fn timestamp_ms(&self) -> i64 {
+        self.t
+    }
+}
+
+impl EODHDTick for EODHDCryptoRT {
+    fn symbol(&self) -> &str {
+        self.s.as_deref().unwrap_or_default()
+    }
+    fn price(&self) -> f64 {
+        self.p
+    }
+    fn quantity(&self) -> f64 {
+        self.q
+    }
+    fn timestamp_ms(&self) -> i64 {
+        self.t
+    }
+}
+
+/// Priced at the mid between bid and ask
+impl EODHDTick for EODHDForexRT {
+    fn symbol(&self) -> &str {
+        &self.s
+    }
+    fn price(&self) -> f64 {
+        (self.a + self.b) / 2.0
+    }
+    fn quantity(&self) -> f64 {
+        1.0
+    }
+    fn timestamp_ms(&self) -> i64 {
+        self.t
+    }
+}
+
+/// When a bar is complete
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EODHDBarKind {
+    /// after a fixed span of tick time, aligned to the
+    /// unix epoch (1 minute bars start at full minutes)
+    Time(Duration),
+    /// after a number of ticks
+    Ticks(usize),
+    /// once the summed [EODHDTick::quantity] reaches the threshold
+    Volume(f64),
+}
+
+/// What happens to ticks older than the
+/// last bar already emitted for their symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDLateTicks {
+    /// leave them out
+    Drop,
+    /// add them to the last emitted bar if they fall into
+    /// it and emit it again, older ones are dropped
+    Revise,
+}
+
+#[derive(Debug, Clone)]
+pub struct EODHDBarOptions {
+    pub kind: EODHDBarKind,
+    pub late: EODHDLateTicks,
+    /// how long time bars wait for delayed ticks after their
+    /// end before the timer emits them, a tick of a later bar
+    /// emits them right away
+    pub grace: Duration,
+    /// also emit the open bars (not complete) at this interval
+    /// if they got new ticks
+    pub partial_interval: Option<Duration>,
+}
+
+impl EODHDBarOptions {
+    pub fn new(kind: EODHDBarKind) -> Self {
+        Self {
+            kind,
+            late: EODHDLateTicks::Drop,
+            grace: Duration::from_secs(1),
+            partial_interval: None,
+        }
+    }
+
+    pub fn with_late(mut self, late: EODHDLateTicks) -> Self {
+        self.late = late;
+        self
+    }
+
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    pub fn with_partial_interval(mut self, interval: Duration) -> Self {
+        self.partial_interval = Some(interval);
+        self
+    }
+}
+
+/// A bar built from socket ticks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EODHDRTBar {
+    /// symbol as sent by the socket
+    pub s: String,
+    /// start in milliseconds, the bucket start of time
+    /// bars, the first tick otherwise
+    pub start: i64,
+    /// end in milliseconds, exclusive for time bars,
+    /// the last tick otherwise
+    pub end: i64,
+    /// price of the earliest tick
+    pub o: f64,
+    pub h: f64,
+    pub l: f64,
+    /// price of the latest tick
+    pub c: f64,
+    /// summed quantity
+    pub v: f64,
+    /// number of ticks
+    pub ticks: usize,
+    /// false for bars emitted before they were finished
+    /// (partial ones and the rest when the ticks end)
+    pub complete: bool,
+}
+
+impl EODHDTimestamped for EODHDRTBar {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        eodhd_timestamp_ms(self.start)
+    }
+}
+
+#[derive(Debug)]
+struct OpenBar {
+    bar: EODHDRTBar,
+    // tick times of the open and close, ticks can arrive out of order
+    first: i64,
+    last: i64,
+    // got ticks since it was last emitted
+    dirty: bool,
+}
+
+impl OpenBar {
+    fn new(symbol: &str, start: i64, end: i64, t: i64, price: f64, quantity: f64) -> Self {
+        Self {
+            bar: EODHDRTBar {
+                s: symbol.to_string(),
+                start,
+                end,
+                o: price,
+                h: price,
+                l: price,
+                c: price,
+                v: quantity,
+                ticks: 1,
+                complete: false,
+            },
+            first: t,
+            last: t,
+            dirty: true,
+        }
+    }
+
+    fn add(&mut self, t: i64, price: f64, quantity: f64) {
+        if t < self.first {
+            self.first = t;
+            self.bar.o = price;
+        }
+        if t >= self.last {
+            self.last = t;
+            self.bar.c = price;
+        }
+        self.bar.h = self.bar.h.max(price);
+        self.bar.l = self.bar.l.min(price);
+        self.bar.v += quantity;
+        self.bar.ticks += 1;
+        self.dirty = true;
+    }
+}
+
+/// Aggregates ticks of any number of symbols into bars.
+/// Used by [EODHDRTChannels::into_bars], can also be fed
+/// directly, e.g. with recorded ticks.
+#[derive(Debug)]
+pub struct EODHDBarBuilder {
+    options: EODHDBarOptions,
+    open: HashMap<String, OpenBar>,
+    // last emitted bar per symbol, for late ticks
+    closed: HashMap<String, OpenBar>,
+}
+
+impl EODHDBarBuilder {
+    pub fn new(options: EODHDBarOptions) -> Self {
+        Self {
+            options,
+            open: HashMap::new(),
+            closed: HashMap::new(),
+        }
+    }
+
+    pub fn options(&self) -> &EODHDBarOptions {
+        &self.options
+    }
+
+    /// Adds `tick`, returns the bars it completed
+    /// (or revised, see [EODHDLateTicks::Revise]).
+    /// Ticks without a price are skipped.
+    pub fn push<T: EODHDTick>(&mut self, tick: &T) -> Vec<EODHDRTBar> {
+        let (symbol, t, price, quantity) = (
+            tick.symbol(),
+            tick.timestamp_ms(),
+            tick.price(),
+            tick.quantity(),
+        );
+        if !price.is_finite() {
+            return vec![];
+        }
+        let quantity = if quantity.is_finite() { quantity } else { 0.0 };
+
+        if let Some(closed) = self.closed.get_mut(symbol) {
+            if t < closed.bar.end {
+                if self.options.late == EODHDLateTicks::Revise && t >= closed.bar.start {
+                    closed.add(t, price, quantity);
+                    return vec![closed.bar.clone()];
+                }
+                debug!("dropped late tick of {} at {}", symbol, t);
+                return vec![];
+            }
+        }
+
+        let mut bars = vec![];
+        match self.options.kind {
+            EODHDBarKind::Time(width) => {
+                let width = (width.as_millis() as i64).max(1);
+                let start = t - t.rem_euclid(width);
+                match self.open.get_mut(symbol) {
+                    Some(open) if start == open.bar.start => open.add(t, price, quantity),
+                    Some(open) if start < open.bar.start => {
+                        debug!("dropped late tick of {} at {}", symbol, t);
+                    }
+                    open => {
+                        if open.is_some() {
+                            bars.extend(self.close(symbol));
+                        }
+                        self.open.insert(
+                            symbol.to_string(),
+                            OpenBar::new(symbol, start, start + width, t, price, quantity),
+                        );
+                    }
+                }
+            }
+            EODHDBarKind::Ticks(_) | EODHDBarKind::Volume(_) => {
+                let open = self
+                    .open
+                    .entry(symbol.to_string())
+                    .and_modify(|open| open.add(t, price, quantity))
+                    .or_insert_with(|| OpenBar::new(symbol, t, t, t, price, quantity));
+                open.bar.start = open.first;
+                open.bar.end = open.last;
+                let full = match self.options.kind {
+                    EODHDBarKind::Ticks(ticks) => open.bar.ticks >= ticks,
+                    EODHDBarKind::Volume(volume) => open.bar.v >= volume,
+                    EODHDBarKind::Time(_) => false,
+                };
+                if full {
+                    bars.extend(self.close(symbol));
+                }
+            }
+        }
+        bars
+    }
+
+    /// Completes the time bars whose end plus
+    /// [EODHDBarOptions::grace] is before `now`
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<EODHDRTBar> {
+        if !matches!(self.options.kind, EODHDBarKind::Time(_)) {
+            return vec![];
+        }
+        let deadline = now.timestamp_millis() - self.options.grace.as_millis() as i64;
+        let expired = self
+            .open
+            .iter()
+            .filter(|(_, open)| open.bar.end <= deadline)
+            .map(|(symbol, _)| symbol.clone())
+            .collect::<Vec<String>>();
+        expired
+            .iter()
+            .filter_map(|symbol| self.close(symbol))
+            .collect()
+    }
+
+    /// Open bars with new ticks since they were last emitted
+    pub fn partials(&mut self) -> Vec<EODHDRTBar> {
+        self.open
+            .values_mut()
+            .filter(|open| open.dirty)
+            .map(|open| {
+                open.dirty = false;
+                open.bar.clone()
+            })
+            .collect()
+    }
+
+    /// Removes and returns all open bars, not complete
+    pub fn flush(&mut self) -> Vec<EODHDRTBar> {
+        self.open.drain().map(|(_, open)| open.bar).collect()
+    }
+
+    fn close(&mut self, symbol: &str) -> Option<EODHDRTBar> {
+        let mut open = self.open.remove(symbol)?;
+        open.bar.complete = true;
+        let bar = open.bar.clone();
+        self.closed.insert(symbol.to_string(), open);
+        Some(bar)
+    }
+}
+
+impl<T: EODHDTick + std::fmt::Debug + Clone + Send + Sync + DeserializeOwned + 'static>
+    EODHDRTChannels<T>
+{
+    /// Builds bars from the ticks in a background task. The
+    /// message channel is kept, so [super::socket::subscribe_rt]
+    /// works on the returned channels as before. Once the ticks
+    /// end the open bars are emitted as not complete.
+    /// * `capacity` - size of the buffer for built bars
+    pub fn into_bars(
+        self,
+        options: EODHDBarOptions,
+        capacity: usize,
+    ) -> EODHDRTChannels<EODHDRTBar> {
+        let (bar_tx, bar_rx) = mpsc::channel(capacity);
+        tokio::spawn(build(
+            self.tick_channel,
+            EODHDBarBuilder::new(options),
+            bar_tx,
+        ));
+        EODHDRTChannels {
+            message_channel: self.message_channel,
+            tick_channel: bar_rx,
+        }
+    }
+}
+
+async fn build<T: EODHDTick>(
+    mut tick_rx: mpsc::Receiver<T>,
+    mut builder: EODHDBarBuilder,
+    bar_tx: mpsc::Sender<EODHDRTBar>,
+) {
+    let expiry_interval = match builder.options().kind {
+        EODHDBarKind::Time(width) => {
+            Some(width.clamp(Duration::from_millis(1), Duration::from_secs(1)))
+        }
+        _ => None,
+    };
+    let partial_interval = builder.options().partial_interval;
+    let mut expiry = tokio::time::interval(expiry_interval.unwrap_or(Duration::from_secs(1)));
+    let mut partial = tokio::time::interval(
+        partial_interval
+            .unwrap_or(Duration::from_secs(1))
+            .max(Duration::from_millis(1)),
+    );
+    expiry.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    partial.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        let bars = tokio::select! {
+            tick = tick_rx.recv() => match tick {
+                Some(tick) => builder.push(&tick),
+                None => {
+                    debug!("tick channel closed, emitting open bars");
+                    for bar in builder.flush() {
+                        if bar_tx.send(bar).await.is_err() {
+                            break;
+                        }
+                    }
+                    return;
+                }
+            },
+            _ = expiry.tick(), if expiry_interval.is_some() => builder.expire(Utc::now()),
+            _ = partial.tick(), if partial_interval.is_some() => builder.partials(),
+            _ = bar_tx.closed() => {
+                debug!("bar channel closed, stop building bars");
+                return;
+            }
+        };
+        for bar in bars {
+            if bar_tx.send(bar).await.is_err() {
+                debug!("bar channel closed, stop building bars");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crypto(t: i64, p: f64, q: f64) -> EODHDCryptoRT {
+        EODHDCryptoRT {
+            s: Some("BTC-USD".to_string()),
+            p,
+            q,
+            dc: 0.0,
+            dd: 0.0,
+            t,
+        }
+    }
+
+    fn minute_bars() -> EODHDBarBuilder {
+        EODHDBarBuilder::new(EODHDBarOptions::new(EODHDBarKind::Time(
+            Duration::from_secs(60),
+        )))
+    }
+
+    fn push_all(builder: &mut EODHDBarBuilder, ticks: &[EODHDCryptoRT]) -> Vec<EODHDRTBar> {
+        ticks.iter().flat_map(|tick| builder.push(tick)).collect()
+    }
+
+    #[test]
+    fn time_bars_are_aligned_and_ordered_by_tick_time() {
+        let mut builder = minute_bars();
+        let bars = push_all(
+            &mut builder,
+            &[
+                crypto(60_500, 10.0, 1.0),
+                crypto(60_100, 9.0, 1.0),
+                crypto(119_999, 12.0, 0.5),
+                crypto(61_000, 15.0, 1.0),
+            ],
+        );
+        assert!(bars.is_empty());
+        let bars = builder.push(&crypto(120_000, 13.0, 1.0));
+        assert_eq!(
+            bars,
+            vec![EODHDRTBar {
+                s: "BTC-USD".to_string(),
+                start: 60_000,
+                end: 120_000,
+                o: 9.0,
+                h: 15.0,
+                l: 9.0,
+                c: 12.0,
+                v: 3.5,
+                ticks: 4,
+                complete: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn late_ticks_are_dropped_by_default() {
+        let mut builder = minute_bars();
+        push_all(
+            &mut builder,
+            &[crypto(60_000, 1.0, 1.0), crypto(120_000, 2.0, 1.0)],
+        );
+        assert!(builder.push(&crypto(90_000, 5.0, 1.0)).is_empty());
+        let bars = builder.push(&crypto(180_000, 3.0, 1.0));
+        assert_eq!((bars[0].start, bars[0].ticks), (120_000, 1));
+    }
+
+    #[test]
+    fn late_ticks_revise_the_last_bar() {
+        let mut builder = EODHDBarBuilder::new(
+            EODHDBarOptions::new(EODHDBarKind::Time(Duration::from_secs(60)))
+                .with_late(EODHDLateTicks::Revise),
+        );
+        push_all(
+            &mut builder,
+            &[crypto(60_000, 1.0, 1.0), crypto(120_000, 2.0, 1.0)],
+        );
+        let revised = builder.push(&crypto(90_000, 5.0, 1.0));
+        assert_eq!(revised.len(), 1);
+        assert_eq!(
+            (
+                revised[0].start,
+                revised[0].h,
+                revised[0].c,
+                revised[0].ticks
+            ),
+            (60_000, 5.0, 5.0, 2)
+        );
+        // older than the last emitted bar
+        assert!(builder.push(&crypto(30_000, 5.0, 1.0)).is_empty());
+    }
+
+    #[test]
+    fn time_bars_expire_after_the_grace_period() {
+        let mut builder = EODHDBarBuilder::new(
+            EODHDBarOptions::new(EODHDBarKind::Time(Duration::from_secs(60)))
+                .with_grace(Duration::from_secs(2)),
+        );
+        builder.push(&crypto(60_000, 1.0, 1.0));
+        let now = |ms| eodhd_timestamp_ms(ms).unwrap();
+        assert!(builder.expire(now(121_999)).is_empty());
+        let bars = builder.expire(now(122_000));
+        assert_eq!(bars.len(), 1);
+        assert!(bars[0].complete);
+        assert!(builder.flush().is_empty());
+    }
+
+    #[test]
+    fn tick_bars() {
+        let mut builder = EODHDBarBuilder::new(EODHDBarOptions::new(EODHDBarKind::Ticks(2)));
+        let ticks = (0..5).map(|t| crypto(t, t as f64, 1.0)).collect::<Vec<_>>();
+        let bars = push_all(&mut builder, &ticks);
+        let bounds = bars
+            .iter()
+            .map(|bar| (bar.start, bar.end, bar.o, bar.c, bar.ticks))
+            .collect::<Vec<_>>();
+        assert_eq!(bounds, vec![(0, 1, 0.0, 1.0, 2), (2, 3, 2.0, 3.0, 2)]);
+        let rest = builder.flush();
+        assert_eq!((rest[0].start, rest[0].complete), (4, false));
+    }
+
+    #[test]
+    fn volume_bars_close_once_the_threshold_is_reached() {
+        let mut builder = EODHDBarBuilder::new(EODHDBarOptions::new(EODHDBarKind::Volume(2.5)));
+        let ticks = (0..6).map(|t| crypto(t, t as f64, 1.0)).collect::<Vec<_>>();
+        let bars = push_all(&mut builder, &ticks);
+        let volumes = bars
+            .iter()
+            .map(|bar| (bar.start, bar.end, bar.v))
+            .collect::<Vec<_>>();
+        assert_eq!(volumes, vec![(0, 2, 3.0), (3, 5, 3.0)]);
+        // a missing quantity adds nothing
+        builder.push(&crypto(6, 1.0, f64::NAN));
+        assert_eq!(builder.flush()[0].v, 0.0);
+    }
+
+    #[test]
+    fn partials_are_emitted_only_after_new_ticks() {
+        let mut builder = minute_bars();
+        builder.push(&crypto(60_000, 1.0, 1.0));
+        let partials = builder.partials();
+        assert_eq!(partials.len(), 1);
+        assert!(!partials[0].complete);
+        assert!(builder.partials().is_empty());
+        builder.push(&crypto(60_001, 2.0, 1.0));
+        assert_eq!(builder.partials()[0].c, 2.0);
+    }
+
+    #[test]
+    fn quotes_are_priced_at_the_mid() {
+        let quote = EODHDForexRT {
+            s: "EURUSD".to_string(),
+            a: 1.1002,
+            b: 1.1,
+            dc: 0.0,
+            dd: 0.0,
+            t: 0,
+        };
+        assert!((quote.price() - 1.1001).abs() < 1e-12);
+        assert_eq!(quote.quantity(), 1.0);
+        let trade = EODHDUSTrade {
+            s: "AAPL".to_string(),
+            p: 190.0,
+            v: None,
+            t: 0,
+        };
+        assert_eq!(trade.quantity(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn into_bars_flushes_when_the_ticks_end() {
+        let (message_tx, _message_rx) = mpsc::channel(1);
+        let (tick_tx, tick_rx) = mpsc::channel(10);
+        let channels = EODHDRTChannels {
+            message_channel: message_tx,
+            tick_channel: tick_rx,
+        };
+        let mut bars = channels.into_bars(EODHDBarOptions::new(EODHDBarKind::Ticks(2)), 10);
+        for t in 0..3 {
+            tick_tx.send(crypto(t, t as f64, 1.0)).await.unwrap();
+        }
+        drop(tick_tx);
+        let mut received = vec![];
+        while let Some(bar) = bars.tick_channel.recv().await {
+            received.push((bar.ticks, bar.complete));
+        }
+        assert_eq!(received, vec![(2, true), (1, false)]);
+    }
+}
@@ -1,45 +1,199 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{env_eodhd_token, BASE_URL};
+use crate::{
+    datetime::{eodhd_timestamp, EODHDTimestamped},
+    eodhd_opt_string_float, eodhd_opt_string_int,
+    rate_limit::EODHDEndpoint,
+    ticker::{parse_ticker, EODHDTicker},
+    EODHDClient, EODHDError,
+};
+
+/// Tickers per request of [EODHDClient::get_delayed_batch],
+/// EODHD recommends at most 15-20
+pub const DELAYED_BATCH_SIZE: usize = 20;
 
+/// Values EODHD doesn't have (e.g. `"NA"` for
+/// illiquid tickers) are `None`.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EODHDDelayed {
+    /// ticker including exchange suffix
     pub code: String,
-    pub timestamp: i64,
-    pub open: f64,
-    pub high: f64,
-    pub low: f64,
-    pub close: f64,
-    pub volume: i64,
-    #[serde(rename = "previousClose")]
-    pub previous_close: f64,
-    pub change: f64,
-    pub change_p: f64,
-}
-
-pub async fn get_delayed(ticker: &str) -> EODHDDelayed {
-    let token = env_eodhd_token();
-    let url = format!(
-        "{base_url}/real-time/{symbol}?fmt=json&api_token={token}",
-        base_url = BASE_URL,
-        symbol = ticker,
-        token = token
-    );
-
-    println!("{:?}", url);
-    let request = reqwest::get(url.clone()).await;
-    if request.is_err() {
-        log::error!(
-            "REQUEST TO EODHD DELAYED FAILED \n{:?}\n with {:?}",
-            url,
-            request.err()
-        );
-        panic!();
+    #[serde(default, with = "eodhd_opt_string_int")]
+    pub timestamp: Option<i64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub open: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub high: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub low: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub close: Option<f64>,
+    #[serde(default, with = "eodhd_opt_string_int")]
+    pub volume: Option<i64>,
+    #[serde(
+        rename = "previousClose",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub previous_close: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change: Option<f64>,
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub change_p: Option<f64>,
+}
+
+impl EODHDTimestamped for EODHDDelayed {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.and_then(eodhd_timestamp)
+    }
+}
+
+/// A single ticker is answered with an object,
+/// several with an array
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(EODHDDelayed),
+    Many(Vec<EODHDDelayed>),
+}
+
+impl EODHDClient {
+    pub async fn get_delayed(&self, ticker: &EODHDTicker) -> Result<EODHDDelayed, EODHDError> {
+        self.get_ticker_json(
+            EODHDEndpoint::Delayed { tickers: 1 },
+            ticker,
+            &format!("real-time/{}", ticker.path_segment()),
+            &[],
+        )
+        .await
+    }
+
+    /// Delayed quotes of all `tickers`, requested in chunks of
+    /// [DELAYED_BATCH_SIZE] (every ticker costs one api call).
+    /// The map is keyed by the given tickers, the ones EODHD
+    /// doesn't answer for are missing.
+    pub async fn get_delayed_batch(
+        &self,
+        tickers: &[EODHDTicker],
+    ) -> Result<HashMap<EODHDTicker, EODHDDelayed>, EODHDError> {
+        let mut quotes = HashMap::with_capacity(tickers.len());
+        for chunk in tickers.chunks(DELAYED_BATCH_SIZE) {
+            let (first, rest) = chunk.split_first().expect("chunks are never empty");
+            let mut query = vec![];
+            if !rest.is_empty() {
+                let rest = rest
+                    .iter()
+                    .map(EODHDTicker::to_string)
+                    .collect::<Vec<String>>()
+                    .join(",");
+                query.push(("s", rest));
+            }
+            let response: OneOrMany = self
+                .get_json(
+                    EODHDEndpoint::Delayed {
+                        tickers: chunk.len() as u32,
+                    },
+                    &format!("real-time/{}", first.path_segment()),
+                    &query,
+                )
+                .await?;
+            let response = match response {
+                OneOrMany::One(quote) => vec![quote],
+                OneOrMany::Many(quotes) => quotes,
+            };
+            quotes.extend(match_requested(chunk, response));
+        }
+        Ok(quotes)
     }
-    let parsed_delayed = request.unwrap().json::<EODHDDelayed>().await;
-    if parsed_delayed.is_err() {
-        log::error!("PARSING DELAYED EODHD FAILED {:?}", parsed_delayed.err());
-        panic!();
+}
+
+/// Pairs the quotes of a batch with the requested tickers.
+/// EODHD echoes the codes in its own case (and spelling),
+/// so they are compared case insensitively, with or without
+/// the exchange. Falls back to the position when nothing
+/// matches and every ticker got a quote.
+fn match_requested(
+    requested: &[EODHDTicker],
+    response: Vec<EODHDDelayed>,
+) -> Vec<(EODHDTicker, EODHDDelayed)> {
+    let complete = response.len() == requested.len();
+    response
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, quote)| {
+            let code = quote.code.trim();
+            let ticker = requested
+                .iter()
+                .find(|ticker| ticker.to_string().eq_ignore_ascii_case(code))
+                .or_else(|| {
+                    requested
+                        .iter()
+                        .find(|ticker| ticker.code.eq_ignore_ascii_case(code))
+                })
+                .or_else(|| requested.get(i).filter(|_| complete))?;
+            Some((ticker.clone(), quote))
+        })
+        .collect()
+}
+
+/// Shorthand for [EODHDClient::get_delayed]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_delayed(ticker: &str) -> Result<EODHDDelayed, EODHDError> {
+    EODHDClient::from_env()?
+        .get_delayed(&parse_ticker(ticker)?)
+        .await
+}
+
+/// Shorthand for [EODHDClient::get_delayed_batch]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_delayed_batch(
+    tickers: &[&str],
+) -> Result<HashMap<EODHDTicker, EODHDDelayed>, EODHDError> {
+    let tickers = tickers
+        .iter()
+        .map(|ticker| parse_ticker(ticker))
+        .collect::<Result<Vec<EODHDTicker>, EODHDError>>()?;
+    EODHDClient::from_env()?.get_delayed_batch(&tickers).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(code: &str) -> EODHDDelayed {
+        serde_json::from_value(serde_json::json!({ "code": code, "close": 1.5 })).unwrap()
+    }
+
+    fn ticker(ticker: &str) -> EODHDTicker {
+        ticker.parse().unwrap()
+    }
+
+    #[test]
+    fn keyed_by_the_requested_ticker() {
+        let requested = [ticker("aapl.us"), ticker("Vod.LSE"), ticker("BMW.XETRA")];
+        let matched = match_requested(
+            &requested,
+            vec![quote("VOD.LSE"), quote("AAPL.US"), quote("bmw")],
+        )
+        .into_iter()
+        .collect::<HashMap<EODHDTicker, EODHDDelayed>>();
+        assert_eq!(matched.len(), 3);
+        assert_eq!(matched[&requested[0]].code, "AAPL.US");
+        assert_eq!(matched[&requested[1]].code, "VOD.LSE");
+        assert_eq!(matched[&requested[2]].code, "bmw");
+    }
+
+    #[test]
+    fn unknown_codes_fall_back_to_the_position() {
+        let requested = [ticker("A.US"), ticker("B.US")];
+        let matched = match_requested(&requested, vec![quote("A.US"), quote("X")]);
+        assert_eq!(matched[1].0, requested[1]);
+
+        // a quote is missing, positions can't be trusted
+        let matched = match_requested(&requested, vec![quote("X")]);
+        assert!(matched.is_empty());
     }
-    parsed_delayed.unwrap()
 }
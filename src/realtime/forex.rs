@@ -1,4 +1,9 @@
-use crate::eodhd_string_float;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    datetime::{eodhd_timestamp_ms, EODHDTimestamped},
+    eodhd_string_float, eodhd_string_int,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6,8 +11,10 @@ pub struct EODHDForexRT {
     /// symbol
     pub s: String,
     /// ask price
+    #[serde(deserialize_with = "eodhd_string_float::deserialize")]
     pub a: f64,
     /// bid price
+    #[serde(deserialize_with = "eodhd_string_float::deserialize")]
     pub b: f64,
     /// daily change percentage (sometimes delivered as string from eodhd)
     #[serde(with = "eodhd_string_float")]
@@ -16,6 +23,7 @@ pub struct EODHDForexRT {
     #[serde(with = "eodhd_string_float")]
     pub dd: f64,
     /// timestamp in milliseconds
+    #[serde(with = "eodhd_string_int")]
     pub t: i64,
 }
 
@@ -36,5 +44,18 @@ pub struct EODHDCryptoRT {
     #[serde(with = "eodhd_string_float")]
     pub dd: f64,
     /// timestamp in milliseconds
+    #[serde(with = "eodhd_string_int")]
     pub t: i64,
 }
+
+impl EODHDTimestamped for EODHDForexRT {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        eodhd_timestamp_ms(self.t)
+    }
+}
+
+impl EODHDTimestamped for EODHDCryptoRT {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        eodhd_timestamp_ms(self.t)
+    }
+}
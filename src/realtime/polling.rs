@@ -0,0 +1,284 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::Stream;
+use log::{debug, error};
+use tokio::sync::mpsc;
+
+use super::delayed::EODHDDelayed;
+use crate::{ticker::EODHDTicker, EODHDClient, EODHDError};
+
+/// Changes the polled tickers of a running
+/// [EODHDPollingChannels] task
+#[derive(Debug, Clone)]
+pub enum EODHDPollingCommand {
+    Subscribe(EODHDTicker),
+    Unsubscribe(EODHDTicker),
+}
+
+/// The parts to handle quotes polled from the delayed
+/// endpoint, for exchanges the realtime sockets don't cover.
+/// Also a [Stream] of the quotes, the background task
+/// stops once this is dropped.
+#[derive(Debug)]
+pub struct EODHDPollingChannels {
+    /// adds or removes tickers from the polled set
+    pub control_channel: mpsc::Sender<EODHDPollingCommand>,
+    /// receives every quote with a new timestamp
+    pub quote_channel: mpsc::Receiver<EODHDDelayed>,
+}
+
+impl Stream for EODHDPollingChannels {
+    type Item = EODHDDelayed;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.quote_channel.poll_recv(cx)
+    }
+}
+
+impl EODHDClient {
+    /// Polls [EODHDClient::get_delayed_batch] for `tickers` every
+    /// `interval` in a background task. Quotes whose timestamp
+    /// didn't change since the last poll are skipped. Requests
+    /// go through the client's rate limiter, failed polls are
+    /// logged and retried at the next interval.
+    /// * `capacity` - size of the buffer for received quotes,
+    ///   at least 1
+    pub fn create_polling_channel(
+        &self,
+        tickers: &[EODHDTicker],
+        interval: Duration,
+        capacity: usize,
+    ) -> EODHDPollingChannels {
+        let (quote_tx, quote_rx) = mpsc::channel(capacity.max(1));
+        let (control_tx, control_rx) = mpsc::channel(capacity.max(1));
+        let client = self.clone();
+        let fetch = move |tickers: Vec<EODHDTicker>| {
+            let client = client.clone();
+            async move { client.get_delayed_batch(&tickers).await }
+        };
+        let tickers = tickers.iter().cloned().collect();
+        tokio::spawn(poll(fetch, tickers, interval, quote_tx, control_rx));
+        EODHDPollingChannels {
+            control_channel: control_tx,
+            quote_channel: quote_rx,
+        }
+    }
+}
+
+/// `fetch` requests the quotes of the given tickers
+async fn poll<F, Fut>(
+    mut fetch: F,
+    mut tickers: BTreeSet<EODHDTicker>,
+    interval: Duration,
+    quote_tx: mpsc::Sender<EODHDDelayed>,
+    mut control_rx: mpsc::Receiver<EODHDPollingCommand>,
+) where
+    F: FnMut(Vec<EODHDTicker>) -> Fut,
+    Fut: Future<Output = Result<HashMap<EODHDTicker, EODHDDelayed>, EODHDError>>,
+{
+    let mut timer = tokio::time::interval(interval);
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_seen: HashMap<EODHDTicker, Option<i64>> = HashMap::new();
+    let mut control_open = true;
+    loop {
+        tokio::select! {
+            command = control_rx.recv(), if control_open => match command {
+                Some(EODHDPollingCommand::Subscribe(ticker)) => {
+                    debug!("polling {}", ticker);
+                    tickers.insert(ticker);
+                }
+                Some(EODHDPollingCommand::Unsubscribe(ticker)) => {
+                    debug!("stopped polling {}", ticker);
+                    last_seen.remove(&ticker);
+                    tickers.remove(&ticker);
+                }
+                // the set of tickers is fixed from now on
+                None => control_open = false,
+            },
+            _ = timer.tick() => {
+                if tickers.is_empty() {
+                    continue;
+                }
+                let requested = tickers.iter().cloned().collect::<Vec<EODHDTicker>>();
+                let quotes = match fetch(requested).await {
+                    Ok(quotes) => quotes,
+                    Err(e) => {
+                        error!("polling delayed quotes failed {}", e);
+                        continue;
+                    }
+                };
+                for (ticker, quote) in quotes {
+                    // unsubscribed while the request was running
+                    if !tickers.contains(&ticker) {
+                        continue;
+                    }
+                    if last_seen.get(&ticker) == Some(&quote.timestamp) {
+                        continue;
+                    }
+                    last_seen.insert(ticker, quote.timestamp);
+                    if quote_tx.send(quote).await.is_err() {
+                        debug!("quote channel closed, stop polling");
+                        return;
+                    }
+                }
+            }
+            _ = quote_tx.closed() => {
+                debug!("quote channel closed, stop polling");
+                return;
+            }
+        }
+    }
+}
+
+/// Shorthand for [EODHDClient::create_polling_channel]
+/// with a client created from `EODHD_TOKEN`.
+pub fn create_polling_channel(
+    tickers: &[EODHDTicker],
+    interval: Duration,
+    capacity: usize,
+) -> Result<EODHDPollingChannels, EODHDError> {
+    Ok(EODHDClient::from_env()?.create_polling_channel(tickers, interval, capacity))
+}
+
+/// Adds `ticker` to the polled set, like
+/// [super::socket::subscribe_rt] for sockets.
+pub async fn subscribe_polling(
+    ticker: &EODHDTicker,
+    channel: &mut EODHDPollingChannels,
+) -> Result<(), EODHDError> {
+    channel
+        .control_channel
+        .send(EODHDPollingCommand::Subscribe(ticker.clone()))
+        .await?;
+    Ok(())
+}
+
+/// Removes `ticker` from the polled set, like
+/// [super::socket::unsubscribe_rt] for sockets.
+pub async fn unsubscribe_polling(
+    ticker: &EODHDTicker,
+    channel: &mut EODHDPollingChannels,
+) -> Result<(), EODHDError> {
+    channel
+        .control_channel
+        .send(EODHDPollingCommand::Unsubscribe(ticker.clone()))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn quote(code: &str, timestamp: i64) -> EODHDDelayed {
+        serde_json::from_value(serde_json::json!({ "code": code, "timestamp": timestamp })).unwrap()
+    }
+
+    /// Polls a fake source answering every ticker with a quote whose
+    /// timestamp is the poll count, returns the requested tickers per poll
+    fn start(
+        tickers: &[EODHDTicker],
+        capacity: usize,
+    ) -> (EODHDPollingChannels, Arc<Mutex<Vec<Vec<EODHDTicker>>>>) {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let recorded = requests.clone();
+        let fetch = move |tickers: Vec<EODHDTicker>| {
+            let mut requests = recorded.lock().unwrap();
+            requests.push(tickers.clone());
+            let timestamp = requests.len() as i64;
+            async move {
+                Ok(tickers
+                    .into_iter()
+                    .map(|ticker| {
+                        let quote = quote(&ticker.to_string(), timestamp);
+                        (ticker, quote)
+                    })
+                    .collect())
+            }
+        };
+        let (quote_tx, quote_rx) = mpsc::channel(capacity.max(1));
+        let (control_tx, control_rx) = mpsc::channel(capacity.max(1));
+        tokio::spawn(poll(
+            fetch,
+            tickers.iter().cloned().collect(),
+            Duration::from_millis(1),
+            quote_tx,
+            control_rx,
+        ));
+        let channels = EODHDPollingChannels {
+            control_channel: control_tx,
+            quote_channel: quote_rx,
+        };
+        (channels, requests)
+    }
+
+    #[tokio::test]
+    async fn delivers_quotes_and_follows_subscriptions() {
+        let aapl: EODHDTicker = "AAPL.US".parse().unwrap();
+        let vod: EODHDTicker = "VOD.LSE".parse().unwrap();
+        let (mut channels, requests) = start(std::slice::from_ref(&aapl), 0);
+
+        let first = channels.quote_channel.recv().await.unwrap();
+        assert_eq!((first.code.as_str(), first.timestamp), ("AAPL.US", Some(1)));
+
+        subscribe_polling(&vod, &mut channels).await.unwrap();
+        unsubscribe_polling(&aapl, &mut channels).await.unwrap();
+        // quotes of polls before the commands arrived may still be buffered
+        loop {
+            let quote = channels.quote_channel.recv().await.unwrap();
+            if quote.code == "VOD.LSE" {
+                break;
+            }
+        }
+        let last = requests.lock().unwrap().last().unwrap().clone();
+        assert_eq!(last, vec![vod]);
+
+        // the task ends and drops the source
+        drop(channels);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while Arc::strong_count(&requests) > 1 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("polling stops once the channels are dropped");
+    }
+
+    #[tokio::test]
+    async fn skips_unchanged_timestamps() {
+        let aapl: EODHDTicker = "AAPL.US".parse().unwrap();
+        let fetch = |tickers: Vec<EODHDTicker>| async move {
+            Ok(tickers
+                .into_iter()
+                .map(|ticker| (ticker, quote("AAPL.US", 7)))
+                .collect())
+        };
+        let (quote_tx, mut quote_rx) = mpsc::channel(4);
+        let (_control_tx, control_rx) = mpsc::channel(1);
+        tokio::spawn(poll(
+            fetch,
+            [aapl].into_iter().collect(),
+            Duration::from_millis(1),
+            quote_tx,
+            control_rx,
+        ));
+        assert_eq!(quote_rx.recv().await.unwrap().timestamp, Some(7));
+        let again = tokio::time::timeout(Duration::from_millis(20), quote_rx.recv()).await;
+        assert!(again.is_err());
+    }
+
+    #[tokio::test]
+    async fn zero_capacity_is_allowed() {
+        let client = EODHDClient::new("token").with_base_url("http://127.0.0.1:9");
+        let channels = client.create_polling_channel(&[], Duration::from_secs(60), 0);
+        assert_eq!(channels.quote_channel.max_capacity(), 1);
+    }
+}
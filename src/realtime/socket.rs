@@ -6,9 +6,9 @@ use tokio_tungstenite::{
     tungstenite::{self, Message},
 };
 
-use crate::env_eodhd_token;
+use std::fmt::Display;
 
-use super::BASE_URL_SOCKET;
+use crate::{ticker::EODHDTicker, EODHDClient, EODHDError};
 
 /// For messages like
 /// Ok(Text("{\"status_code\":200,\"message\":\"Authorized\"}"))
@@ -26,14 +26,14 @@ pub enum EODHDSocketKind {
     Trade,
 }
 
-impl ToString for EODHDSocketKind {
-    fn to_string(&self) -> String {
-        match self {
-            EODHDSocketKind::Forex => "forex".to_string(),
-            EODHDSocketKind::Crypto => "crypto".to_string(),
-            EODHDSocketKind::Quote => "us-quote".to_string(),
-            EODHDSocketKind::Trade => "us".to_string(),
-        }
+impl Display for EODHDSocketKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EODHDSocketKind::Forex => "forex",
+            EODHDSocketKind::Crypto => "crypto",
+            EODHDSocketKind::Quote => "us-quote",
+            EODHDSocketKind::Trade => "us",
+        })
     }
 }
 
@@ -50,136 +50,158 @@ pub struct EODHDRTChannels<T: std::fmt::Debug + Clone + Send + Sync + Deserializ
     pub tick_channel: tokio::sync::mpsc::Receiver<T>,
 }
 
-/// The initial function to call for setting up
-/// a websocket connection to EODHD's realtime offerings.
-/// One has to open different sockets for different kinds
-/// of realtime streams (Forex, Quotes etc.)
-/// * `T` - Type of ticks for the socket kind
-pub async fn create_socket_channel<
-    T: std::fmt::Debug + Clone + Send + Sync + DeserializeOwned + 'static,
->(
-    capacity: usize,
-    kind: EODHDSocketKind,
-) -> Result<EODHDRTChannels<T>, Box<dyn std::error::Error + Send + Sync>> {
-    let (tick_tx, tick_tr) = tokio::sync::mpsc::channel(capacity);
-    let (message_tx, mut message_tr) =
-        tokio::sync::mpsc::channel::<tokio_tungstenite::tungstenite::Message>(capacity);
+impl EODHDClient {
+    /// The initial function to call for setting up
+    /// a websocket connection to EODHD's realtime offerings.
+    /// One has to open different sockets for different kinds
+    /// of realtime streams (Forex, Quotes etc.)
+    /// * `T` - Type of ticks for the socket kind
+    pub async fn create_socket_channel<
+        T: std::fmt::Debug + Clone + Send + Sync + DeserializeOwned + 'static,
+    >(
+        &self,
+        capacity: usize,
+        kind: EODHDSocketKind,
+    ) -> Result<EODHDRTChannels<T>, EODHDError> {
+        let (tick_tx, tick_tr) = tokio::sync::mpsc::channel(capacity);
+        let (message_tx, mut message_tr) =
+            tokio::sync::mpsc::channel::<tokio_tungstenite::tungstenite::Message>(capacity);
 
-    let token = env_eodhd_token();
-    let url_string = format!(
-        "{base_url}/{kind}?api_token={api_token}",
-        base_url = BASE_URL_SOCKET,
-        kind = kind.to_string(),
-        api_token = token
-    );
+        let url_string = format!(
+            "{base_url}/{kind}?api_token={api_token}",
+            base_url = self.socket_url(),
+            kind = kind,
+            api_token = self.token()
+        );
 
-    let url = url::Url::parse(&url_string).unwrap();
-    match connect_async(url).await {
-        Ok((socket, _response)) => {
-            let (mut socket_tx, mut socket_tr) = socket.split();
+        match connect_async(url_string).await {
+            Ok((socket, _response)) => {
+                let (mut socket_tx, mut socket_tr) = socket.split();
 
-            let message_tx_clone = message_tx.clone();
-            // task handles new (un)subscriptions and other messages
-            tokio::spawn(async move {
-                while let Some(message) = message_tr.recv().await {
-                    match socket_tx.send(message).await {
-                        Ok(o) => {
-                            info!("Send socket a message {:?}", o);
-                        }
-                        Err(e) => {
-                            error!("There was an error while reading from the socket {:#?}", e);
+                let message_tx_clone = message_tx.clone();
+                // task handles new (un)subscriptions and other messages
+                tokio::spawn(async move {
+                    while let Some(message) = message_tr.recv().await {
+                        match socket_tx.send(message).await {
+                            Ok(o) => {
+                                info!("Send socket a message {:?}", o);
+                            }
+                            Err(e) => {
+                                error!("There was an error while reading from the socket {:#?}", e);
+                            }
                         }
                     }
-                }
-            });
-            // saves new ticks and takes care
-            // of messages the user doesn't need
-            tokio::spawn(async move {
-                while let Some(res) = socket_tr.next().await {
-                    match res {
-                        Err(e) => {
-                            error!("There was an error while reading from the socket {:#?}", e);
-                        }
-                        Ok(message) => {
-                            debug!("Socket got a message {:?}", message);
-                            match message {
-                                tungstenite::Message::Ping(_) => {
-                                    let pong = message_tx_clone.send(Message::Pong(vec![])).await;
-                                    if let Err(e) = pong {
-                                        error!("There was an error while reading from the socket {:#?}", e);
-                                    }
-                                }
-                                tungstenite::Message::Text(pip_string) => {
-                                    if pip_string.contains("status_code") {
-                                        let status_message_parsed =
-                                            serde_json::from_str::<StatusMessage>(&pip_string);
-                                        match status_message_parsed {
-                                            Ok(status_message) => {
-                                                info!(
-                                                    "Socket status {:?} with message {:?}",
-                                                    status_message.status_code,
-                                                    status_message.message
-                                                );
-                                            }
-                                            Err(e) => {
-                                                error!("There was an error while reading from the socket {:#?}", e);
-                                            }
+                });
+                // saves new ticks and takes care
+                // of messages the user doesn't need
+                tokio::spawn(async move {
+                    while let Some(res) = socket_tr.next().await {
+                        match res {
+                            Err(e) => {
+                                error!("There was an error while reading from the socket {:#?}", e);
+                            }
+                            Ok(message) => {
+                                debug!("Socket got a message {:?}", message);
+                                match message {
+                                    tungstenite::Message::Ping(_) => {
+                                        let pong =
+                                            message_tx_clone.send(Message::Pong(vec![])).await;
+                                        if let Err(e) = pong {
+                                            error!("There was an error while reading from the socket {:#?}", e);
                                         }
-                                        continue;
                                     }
-                                    let parsed_pip = serde_json::from_str::<T>(pip_string.as_str());
-                                    match parsed_pip {
-                                        Ok(parsed_pip) => {
-                                            match tick_tx.send(parsed_pip.clone()).await {
-                                                Ok(_) => debug!(
-                                                    "Send a pip over tick channel {:#?}",
-                                                    parsed_pip
-                                                ),
+                                    tungstenite::Message::Text(pip_string) => {
+                                        if pip_string.contains("status_code") {
+                                            let status_message_parsed =
+                                                serde_json::from_str::<StatusMessage>(&pip_string);
+                                            match status_message_parsed {
+                                                Ok(status_message) => {
+                                                    info!(
+                                                        "Socket status {:?} with message {:?}",
+                                                        status_message.status_code,
+                                                        status_message.message
+                                                    );
+                                                }
                                                 Err(e) => {
-                                                    error!("error while sending pip {:#?}", e);
+                                                    error!("There was an error while reading from the socket {:#?}", e);
                                                 }
                                             }
+                                            continue;
                                         }
-                                        Err(e) => {
-                                            error!("Unable to decode message from socket {:#?}", e);
+                                        let parsed_pip =
+                                            serde_json::from_str::<T>(pip_string.as_str());
+                                        match parsed_pip {
+                                            Ok(parsed_pip) => {
+                                                match tick_tx.send(parsed_pip.clone()).await {
+                                                    Ok(_) => debug!(
+                                                        "Send a pip over tick channel {:#?}",
+                                                        parsed_pip
+                                                    ),
+                                                    Err(e) => {
+                                                        error!("error while sending pip {:#?}", e);
+                                                    }
+                                                }
+                                            }
+                                            Err(e) => {
+                                                error!(
+                                                    "Unable to decode message from socket {:#?}",
+                                                    e
+                                                );
+                                            }
                                         }
                                     }
-                                }
-                                unknown => {
-                                    error!(
-                                        "There was an unknown type in the socket {:#?}",
-                                        unknown
-                                    );
+                                    unknown => {
+                                        error!(
+                                            "There was an unknown type in the socket {:#?}",
+                                            unknown
+                                        );
+                                    }
                                 }
                             }
                         }
                     }
-                }
-            });
-        }
-        Err(e) => {
-            return Err(Box::new(e));
-        }
-    };
-    Ok(EODHDRTChannels {
-        message_channel: message_tx,
-        tick_channel: tick_tr,
-    })
+                });
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
+        };
+        Ok(EODHDRTChannels {
+            message_channel: message_tx,
+            tick_channel: tick_tr,
+        })
+    }
+}
+
+/// Shorthand for [EODHDClient::create_socket_channel]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn create_socket_channel<
+    T: std::fmt::Debug + Clone + Send + Sync + DeserializeOwned + 'static,
+>(
+    capacity: usize,
+    kind: EODHDSocketKind,
+) -> Result<EODHDRTChannels<T>, EODHDError> {
+    EODHDClient::from_env()?
+        .create_socket_channel(capacity, kind)
+        .await
 }
 
+/// Subscribes to the ticks of `ticker`, e.g. "AAPL.US" on
+/// [EODHDSocketKind::Trade] or "EURUSD.FOREX" on
+/// [EODHDSocketKind::Forex] (the suffix isn't sent).
 pub async fn subscribe_rt<T: std::fmt::Debug + Clone + Send + Sync + DeserializeOwned>(
-    ticker: &str,
+    ticker: &EODHDTicker,
     channel: &mut EODHDRTChannels<T>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(), EODHDError> {
     let subscribe_msg = format!(
         "{{\"action\": \"subscribe\", \"symbols\": \"{symbol}\"}}",
-        symbol = ticker
+        symbol = ticker.socket_symbol()
     );
     let message = Message::text(subscribe_msg.to_string());
     let response = channel.message_channel.send(message).await;
     if let Err(e) = response {
         error!("Unable to subscribe for symbol {:?}", ticker);
-        return Err(Box::new(e));
+        return Err(e.into());
     }
     Ok(())
 }
@@ -187,18 +209,18 @@ pub async fn subscribe_rt<T: std::fmt::Debug + Clone + Send + Sync + Deserialize
 pub async fn unsubscribe_rt<
     T: std::fmt::Debug + Clone + Send + Sync + DeserializeOwned + 'static,
 >(
-    ticker: &str,
+    ticker: &EODHDTicker,
     channel: &mut EODHDRTChannels<T>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(), EODHDError> {
     let unsubscribe_msg = format!(
         "{{\"action\": \"unsubscribe\", \"symbols\": \"{symbol}\"}}",
-        symbol = ticker
+        symbol = ticker.socket_symbol()
     );
     let message = Message::text(unsubscribe_msg.to_string());
     let unsubscribe_res = channel.message_channel.send(message).await;
     if let Err(e) = unsubscribe_res {
         error!("Unsubscribe for ticker {} failed", ticker);
-        return Err(Box::new(e));
+        return Err(e.into());
     }
     Ok(())
 }
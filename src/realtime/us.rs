@@ -1,12 +1,24 @@
 use serde::{Deserialize, Serialize};
 
+use chrono::{DateTime, Utc};
+
+use crate::{
+    datetime::{eodhd_timestamp_ms, EODHDTimestamped},
+    eodhd_opt_string_float, eodhd_string_float, eodhd_string_int,
+};
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EODHDUSTrade {
     // ticker code
     pub s: String,
     // price
+    #[serde(deserialize_with = "eodhd_string_float::deserialize")]
     pub p: f64,
+    // size of the trade
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub v: Option<f64>,
     // timestamp in milliseconds
+    #[serde(with = "eodhd_string_int")]
     pub t: i64,
 }
 
@@ -15,15 +27,30 @@ pub struct EODHDUSQuote {
     // ticker code
     pub s: String,
     // ask price
+    #[serde(deserialize_with = "eodhd_string_float::deserialize")]
     pub ap: f64,
     // ask size
-    #[serde(rename = "as")]
+    #[serde(rename = "as", deserialize_with = "eodhd_string_float::deserialize")]
     pub av: f64,
     // bid price
+    #[serde(deserialize_with = "eodhd_string_float::deserialize")]
     pub bp: f64,
     // bid size
-    #[serde(rename = "bs")]
+    #[serde(rename = "bs", deserialize_with = "eodhd_string_float::deserialize")]
     pub bv: f64,
     // timestamp in milliseconds
+    #[serde(with = "eodhd_string_int")]
     pub t: i64,
 }
+
+impl EODHDTimestamped for EODHDUSTrade {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        eodhd_timestamp_ms(self.t)
+    }
+}
+
+impl EODHDTimestamped for EODHDUSQuote {
+    fn time(&self) -> Option<DateTime<Utc>> {
+        eodhd_timestamp_ms(self.t)
+    }
+}
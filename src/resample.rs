@@ -0,0 +1,600 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::{
+    end_of_period::{EODHDAdjustedEndOfPeriod, EODHDEndOfPeriod},
+    historic_intraday::EODHDIntradayBar,
+};
+
+/// A bar that can be resampled. Times are exchange
+/// local so buckets line up with the sessions.
+pub trait EODHDOHLCV {
+    /// start of the bar
+    fn start(&self) -> NaiveDateTime;
+    fn open(&self) -> f64;
+    fn high(&self) -> f64;
+    fn low(&self) -> f64;
+    fn close(&self) -> f64;
+    fn volume(&self) -> f64;
+    /// false for bars of a day or longer, the
+    /// [EODHDResampleOptions::session] doesn't apply to them
+    fn is_intraday(&self) -> bool {
+        true
+    }
+}
+
+/// Unadjusted, like the open, high and
+/// low EODHD delivers
+impl EODHDOHLCV for EODHDEndOfPeriod {
+    fn start(&self) -> NaiveDateTime {
+        self.date.and_time(NaiveTime::MIN)
+    }
+    fn open(&self) -> f64 {
+        self.o
+    }
+    fn high(&self) -> f64 {
+        self.h
+    }
+    fn low(&self) -> f64 {
+        self.l
+    }
+    fn close(&self) -> f64 {
+        self.unadjusted_close
+    }
+    fn volume(&self) -> f64 {
+        self.v as f64
+    }
+    fn is_intraday(&self) -> bool {
+        false
+    }
+}
+
+impl EODHDOHLCV for EODHDAdjustedEndOfPeriod {
+    fn start(&self) -> NaiveDateTime {
+        self.date.and_time(NaiveTime::MIN)
+    }
+    fn open(&self) -> f64 {
+        self.o
+    }
+    fn high(&self) -> f64 {
+        self.h
+    }
+    fn low(&self) -> f64 {
+        self.l
+    }
+    fn close(&self) -> f64 {
+        self.c
+    }
+    fn volume(&self) -> f64 {
+        self.v
+    }
+    fn is_intraday(&self) -> bool {
+        false
+    }
+}
+
+/// In the exchange's time, see [EODHDIntradayBar::local]
+impl EODHDOHLCV for EODHDIntradayBar {
+    fn start(&self) -> NaiveDateTime {
+        self.local.naive_local()
+    }
+    fn open(&self) -> f64 {
+        self.o
+    }
+    fn high(&self) -> f64 {
+        self.h
+    }
+    fn low(&self) -> f64 {
+        self.l
+    }
+    fn close(&self) -> f64 {
+        self.c
+    }
+    fn volume(&self) -> f64 {
+        self.v as f64
+    }
+}
+
+/// The length of the resampled bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDResampleRule {
+    /// counted from the [EODHDResampleOptions::anchor] on
+    /// 1970-01-01, widths not dividing a day run across midnight
+    Minutes(u32),
+    Hours(u32),
+    Daily,
+    /// starting at [EODHDResampleOptions::week_start]
+    Weekly,
+    Monthly,
+    Quarterly,
+}
+
+impl EODHDResampleRule {
+    /// Fixed length rules (up to a day)
+    fn width(&self) -> Option<Duration> {
+        match self {
+            EODHDResampleRule::Minutes(m) => Some(Duration::minutes((*m).max(1) as i64)),
+            EODHDResampleRule::Hours(h) => Some(Duration::hours((*h).max(1) as i64)),
+            EODHDResampleRule::Daily => Some(Duration::days(1)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EODHDResampleOptions {
+    pub rule: EODHDResampleRule,
+    /// time of day buckets are aligned to, e.g. 17:00 for
+    /// forex days, ignored if `session` is given
+    /// (days, weeks... then follow the trading date)
+    pub anchor: NaiveTime,
+    /// first day of weekly bars
+    pub week_start: Weekday,
+    /// regular trading hours (open, close), intraday bars
+    /// outside are dropped for every rule (daily ones are
+    /// kept), intraday buckets start at the open and the
+    /// last one of a day ends at the close
+    pub session: Option<(NaiveTime, NaiveTime)>,
+}
+
+impl EODHDResampleOptions {
+    pub fn new(rule: EODHDResampleRule) -> Self {
+        Self {
+            rule,
+            anchor: NaiveTime::MIN,
+            week_start: Weekday::Mon,
+            session: None,
+        }
+    }
+
+    pub fn with_anchor(mut self, anchor: NaiveTime) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn with_week_start(mut self, week_start: Weekday) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    pub fn with_session(mut self, open: NaiveTime, close: NaiveTime) -> Self {
+        self.session = Some((open, close));
+        self
+    }
+
+    fn in_session(&self, time: NaiveDateTime) -> bool {
+        match self.session {
+            Some((open, close)) => time.time() >= open && time.time() < close,
+            None => true,
+        }
+    }
+
+    /// Start and end of the bucket `time` falls into
+    fn bucket(&self, time: NaiveDateTime) -> (NaiveDateTime, NaiveDateTime) {
+        let width = match (self.rule, self.session) {
+            (EODHDResampleRule::Daily, Some(_)) => None,
+            (rule, _) => rule.width(),
+        };
+        if let Some(width) = width {
+            let origin = match self.session {
+                Some((open, _)) => time.date().and_time(open),
+                // fixed, so buckets don't restart (and overlap) at midnight
+                None => NaiveDate::from_ymd_opt(1970, 1, 1)
+                    .expect("valid date")
+                    .and_time(self.anchor),
+            };
+            let seconds = width.num_seconds();
+            let index = (time - origin).num_seconds().div_euclid(seconds);
+            let start = origin + Duration::seconds(index * seconds);
+            let mut end = start + width;
+            if let Some((_, close)) = self.session {
+                let close = start.date().and_time(close);
+                if start < close && close < end {
+                    end = close;
+                }
+            }
+            return (start, end);
+        }
+        // the trading date with a session, otherwise bars
+        // before the anchor time belong to the previous day
+        let anchor = match self.session {
+            Some(_) => NaiveTime::MIN,
+            None => self.anchor,
+        };
+        let day = if time.time() < anchor {
+            time.date() - Duration::days(1)
+        } else {
+            time.date()
+        };
+        let (start, end) = match self.rule {
+            EODHDResampleRule::Weekly => {
+                let offset = (7 + day.weekday().num_days_from_monday()
+                    - self.week_start.num_days_from_monday())
+                    % 7;
+                let start = day - Duration::days(offset as i64);
+                (start, start + Duration::days(7))
+            }
+            EODHDResampleRule::Monthly => {
+                let start = first_of_month(day.year(), day.month());
+                (start, add_months(start, 1))
+            }
+            EODHDResampleRule::Quarterly => {
+                let month = (day.month0() / 3) * 3 + 1;
+                let start = first_of_month(day.year(), month);
+                (start, add_months(start, 3))
+            }
+            // daily with a session
+            _ => (day, day + Duration::days(1)),
+        };
+        (start.and_time(anchor), end.and_time(anchor))
+    }
+}
+
+/// `bars` aggregated to [EODHDResampleOptions::rule]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EODHDResampledBar {
+    /// start of the bucket (inclusive)
+    pub start: NaiveDateTime,
+    /// end of the bucket (exclusive)
+    pub end: NaiveDateTime,
+    /// open of the first bar
+    pub o: f64,
+    /// highest high
+    pub h: f64,
+    /// lowest low
+    pub l: f64,
+    /// close of the last bar
+    pub c: f64,
+    /// summed volume
+    pub v: f64,
+    /// number of aggregated bars
+    pub count: usize,
+}
+
+impl EODHDOHLCV for EODHDResampledBar {
+    fn start(&self) -> NaiveDateTime {
+        self.start
+    }
+    fn open(&self) -> f64 {
+        self.o
+    }
+    fn high(&self) -> f64 {
+        self.h
+    }
+    fn low(&self) -> f64 {
+        self.l
+    }
+    fn close(&self) -> f64 {
+        self.c
+    }
+    fn volume(&self) -> f64 {
+        self.v
+    }
+    fn is_intraday(&self) -> bool {
+        self.end - self.start < Duration::days(1)
+    }
+}
+
+/// Aggregates `bars` into buckets of `options.rule`, with
+/// the first open, highest high, lowest low, last close and
+/// summed volume. Bars are sorted by start first, bars without
+/// a price (e.g. flagged incomplete ones) are skipped, empty
+/// buckets aren't emitted.
+pub fn resample<B: EODHDOHLCV>(
+    bars: &[B],
+    options: &EODHDResampleOptions,
+) -> Vec<EODHDResampledBar> {
+    let mut bars = bars
+        .iter()
+        .filter(|bar| {
+            !(bar.open().is_nan()
+                || bar.high().is_nan()
+                || bar.low().is_nan()
+                || bar.close().is_nan())
+        })
+        .filter(|bar| !bar.is_intraday() || options.in_session(bar.start()))
+        .collect::<Vec<&B>>();
+    bars.sort_by_key(|bar| bar.start());
+
+    let mut resampled: Vec<EODHDResampledBar> = vec![];
+    for bar in bars {
+        let (start, end) = options.bucket(bar.start());
+        match resampled.last_mut() {
+            Some(current) if current.start == start => {
+                current.h = current.h.max(bar.high());
+                current.l = current.l.min(bar.low());
+                current.c = bar.close();
+                current.v += bar.volume();
+                current.count += 1;
+            }
+            _ => resampled.push(EODHDResampledBar {
+                start,
+                end,
+                o: bar.open(),
+                h: bar.high(),
+                l: bar.low(),
+                c: bar.close(),
+                v: bar.volume(),
+                count: 1,
+            }),
+        }
+    }
+    resampled
+}
+
+fn first_of_month(year: i32, month: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, 1).expect("valid month")
+}
+
+fn add_months(date: NaiveDate, months: u32) -> NaiveDate {
+    let month0 = date.month0() + months;
+    first_of_month(date.year() + (month0 / 12) as i32, month0 % 12 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn midnight(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_time(NaiveTime::MIN)
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn bar(start: NaiveDateTime, price: f64) -> EODHDResampledBar {
+        EODHDResampledBar {
+            start,
+            end: start,
+            o: price,
+            h: price + 1.0,
+            l: price - 1.0,
+            c: price,
+            v: 10.0,
+            count: 1,
+        }
+    }
+
+    /// One bar per minute of 2024-01-02 (a tuesday), priced by its minute of the day
+    fn minutes() -> Vec<EODHDResampledBar> {
+        (0..24 * 60)
+            .map(|minute| bar(at(1, 2, 0, 0) + Duration::minutes(minute), minute as f64))
+            .collect()
+    }
+
+    fn daily(from: NaiveDate, days: i64) -> Vec<EODHDResampledBar> {
+        (0..days)
+            .map(|day| {
+                bar(
+                    (from + Duration::days(day)).and_time(NaiveTime::MIN),
+                    day as f64,
+                )
+            })
+            .collect()
+    }
+
+    fn regular_hours(rule: EODHDResampleRule) -> EODHDResampleOptions {
+        EODHDResampleOptions::new(rule).with_session(time(9, 30), time(16, 0))
+    }
+
+    #[test]
+    fn aggregates_ohlcv() {
+        let bars = [
+            bar(at(1, 2, 10, 2), 5.0),
+            bar(at(1, 2, 10, 0), 3.0),
+            bar(at(1, 2, 10, 1), 9.0),
+        ];
+        let resampled = resample(
+            &bars,
+            &EODHDResampleOptions::new(EODHDResampleRule::Minutes(5)),
+        );
+        assert_eq!(resampled.len(), 1);
+        let bar = resampled[0];
+        assert_eq!((bar.start, bar.end), (at(1, 2, 10, 0), at(1, 2, 10, 5)));
+        assert_eq!((bar.o, bar.h, bar.l, bar.c), (3.0, 10.0, 2.0, 5.0));
+        assert_eq!((bar.v, bar.count), (30.0, 3));
+    }
+
+    #[test]
+    fn skips_bars_without_price() {
+        let bars = [bar(at(1, 2, 10, 0), f64::NAN), bar(at(1, 2, 10, 1), 4.0)];
+        let resampled = resample(
+            &bars,
+            &EODHDResampleOptions::new(EODHDResampleRule::Minutes(5)),
+        );
+        assert_eq!(resampled[0].o, 4.0);
+        assert_eq!(resampled[0].count, 1);
+    }
+
+    #[test]
+    fn minutes_in_session() {
+        let resampled = resample(&minutes(), &regular_hours(EODHDResampleRule::Minutes(15)));
+        assert_eq!(resampled.len(), 26);
+        assert_eq!(resampled[0].start, at(1, 2, 9, 30));
+        assert_eq!(resampled[0].o, 570.0);
+        assert_eq!(resampled[0].count, 15);
+        assert_eq!(resampled[25].end, at(1, 2, 16, 0));
+        assert_eq!(resampled[25].c, 959.0);
+    }
+
+    #[test]
+    fn hours_in_session_end_at_the_close() {
+        let resampled = resample(&minutes(), &regular_hours(EODHDResampleRule::Hours(4)));
+        let bounds = resampled
+            .iter()
+            .map(|bar| (bar.start, bar.end, bar.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bounds,
+            vec![
+                (at(1, 2, 9, 30), at(1, 2, 13, 30), 240),
+                (at(1, 2, 13, 30), at(1, 2, 16, 0), 150)
+            ]
+        );
+    }
+
+    #[test]
+    fn hours_without_session_follow_the_anchor() {
+        let options =
+            EODHDResampleOptions::new(EODHDResampleRule::Hours(4)).with_anchor(time(1, 0));
+        let resampled = resample(&minutes(), &options);
+        assert_eq!(resampled.len(), 7);
+        // before the anchor, the bucket starts the day before
+        assert_eq!(
+            (resampled[0].start, resampled[0].count),
+            (at(1, 1, 21, 0), 60)
+        );
+        assert_eq!(resampled[1].start, at(1, 2, 1, 0));
+    }
+
+    #[test]
+    fn calendar_rules_with_session_use_the_trading_date() {
+        // monday 2024-01-08 pre market, regular and after hours
+        let bars = [
+            bar(at(1, 5, 15, 0), 1.0),
+            bar(at(1, 8, 8, 0), 2.0),
+            bar(at(1, 8, 10, 0), 3.0),
+            bar(at(1, 8, 17, 0), 4.0),
+        ];
+        let resampled = resample(&bars, &regular_hours(EODHDResampleRule::Weekly));
+        assert_eq!(resampled.len(), 2);
+        assert_eq!(
+            (resampled[0].start, resampled[0].end, resampled[0].c),
+            (at(1, 1, 0, 0), at(1, 8, 0, 0), 1.0)
+        );
+        assert_eq!(
+            (resampled[1].start, resampled[1].o, resampled[1].count),
+            (at(1, 8, 0, 0), 3.0, 1)
+        );
+
+        let bars = [bar(at(1, 31, 15, 0), 1.0), bar(at(2, 1, 8, 0), 2.0)];
+        let resampled = resample(&bars, &regular_hours(EODHDResampleRule::Monthly));
+        assert_eq!(resampled.len(), 1);
+        assert_eq!(resampled[0].c, 1.0);
+    }
+
+    #[test]
+    fn weekly_with_week_start() {
+        // 2024-01-01 is a monday
+        let bars = daily(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 14);
+        let resampled = resample(&bars, &EODHDResampleOptions::new(EODHDResampleRule::Weekly));
+        assert_eq!(resampled.len(), 2);
+        assert_eq!(resampled[0].start, at(1, 1, 0, 0));
+        assert_eq!(resampled[0].count, 7);
+
+        let options =
+            EODHDResampleOptions::new(EODHDResampleRule::Weekly).with_week_start(Weekday::Sun);
+        let resampled = resample(&bars, &options);
+        let bounds = resampled
+            .iter()
+            .map(|bar| (bar.start, bar.end, bar.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bounds,
+            vec![
+                (midnight(2023, 12, 31), at(1, 7, 0, 0), 6),
+                (at(1, 7, 0, 0), at(1, 14, 0, 0), 7),
+                (at(1, 14, 0, 0), at(1, 21, 0, 0), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_and_quarterly_roll_over_years() {
+        let bars = daily(NaiveDate::from_ymd_opt(2023, 11, 15).unwrap(), 120);
+        let monthly = resample(
+            &bars,
+            &EODHDResampleOptions::new(EODHDResampleRule::Monthly),
+        );
+        let starts = monthly.iter().map(|bar| bar.start).collect::<Vec<_>>();
+        assert_eq!(starts[0], midnight(2023, 11, 1));
+        assert_eq!(starts[2], at(1, 1, 0, 0));
+        assert_eq!(monthly[1].end, at(1, 1, 0, 0));
+        assert_eq!(monthly[1].count, 31);
+        assert_eq!(monthly[3].count, 29);
+
+        let quarterly = resample(
+            &bars,
+            &EODHDResampleOptions::new(EODHDResampleRule::Quarterly),
+        );
+        let bounds = quarterly
+            .iter()
+            .map(|bar| (bar.start, bar.end))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bounds,
+            vec![
+                (midnight(2023, 10, 1), at(1, 1, 0, 0)),
+                (at(1, 1, 0, 0), at(4, 1, 0, 0))
+            ]
+        );
+        assert_eq!(quarterly[0].count + quarterly[1].count, 120);
+    }
+
+    #[test]
+    fn session_keeps_daily_bars() {
+        let eod = |day: u32, close: f64| EODHDEndOfPeriod {
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            o: close,
+            h: close,
+            l: close,
+            unadjusted_close: close,
+            c: close,
+            v: 100,
+        };
+        // friday to tuesday
+        let bars = [eod(5, 1.0), eod(8, 2.0), eod(9, 3.0)];
+
+        let weekly = resample(&bars, &regular_hours(EODHDResampleRule::Weekly));
+        let bounds = weekly
+            .iter()
+            .map(|bar| (bar.start, bar.c, bar.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bounds,
+            vec![(at(1, 1, 0, 0), 1.0, 1), (at(1, 8, 0, 0), 3.0, 2)]
+        );
+
+        let daily = resample(&bars, &regular_hours(EODHDResampleRule::Daily));
+        let starts = daily.iter().map(|bar| bar.start).collect::<Vec<_>>();
+        assert_eq!(starts, vec![at(1, 5, 0, 0), at(1, 8, 0, 0), at(1, 9, 0, 0)]);
+        assert_eq!(daily[0].end, at(1, 6, 0, 0));
+    }
+
+    #[test]
+    fn widths_not_dividing_a_day_do_not_overlap() {
+        // two days of minutes
+        let bars = (0..2 * 24 * 60)
+            .map(|minute| bar(at(1, 2, 0, 0) + Duration::minutes(minute), 1.0))
+            .collect::<Vec<_>>();
+        let resampled = resample(
+            &bars,
+            &EODHDResampleOptions::new(EODHDResampleRule::Hours(5)),
+        );
+        for pair in resampled.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+        assert_eq!(
+            resampled.iter().map(|bar| bar.count).sum::<usize>(),
+            bars.len()
+        );
+        // 2024-01-02 00:00 is 473_376 hours after the epoch, an hour into its bucket
+        assert_eq!(resampled[0].start, at(1, 1, 23, 0));
+        assert_eq!(resampled[0].count, 240);
+        assert_eq!(
+            (resampled[4].start, resampled[4].end),
+            (at(1, 2, 19, 0), at(1, 3, 0, 0))
+        );
+        let last = resampled.last().unwrap();
+        assert_eq!((last.start, last.end), (at(1, 3, 20, 0), at(1, 4, 1, 0)));
+    }
+}
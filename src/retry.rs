@@ -0,0 +1,133 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, SystemTime},
+};
+
+use crate::EODHDError;
+
+/// Decides if and when a failed request is sent again.
+/// The delay before retry `n` (starting at 0) is
+/// `min(max_delay, base_delay * 2^n)`, with jitter
+/// a random duration between zero and that value.
+#[derive(Debug, Clone)]
+pub struct EODHDRetryPolicy {
+    /// attempts including the first request, 1 disables retries
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// randomize delays so concurrent jobs don't retry in lockstep
+    pub jitter: bool,
+    /// status codes worth another try, 429 also covers
+    /// [EODHDError::RateLimited] (but not
+    /// [EODHDError::DailyLimitReached], which can't succeed
+    /// before the quota resets)
+    pub retry_statuses: Vec<u16>,
+    /// retry timeouts and connection failures
+    pub retry_transport: bool,
+    /// wait as long as the `Retry-After` header of a 429 says
+    /// (capped at `max_delay`) instead of the backoff
+    pub respect_retry_after: bool,
+}
+
+impl Default for EODHDRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            retry_statuses: vec![429, 500, 502, 503, 504],
+            retry_transport: true,
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl EODHDRetryPolicy {
+    /// Every request is sent exactly once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn is_retryable(&self, error: &EODHDError) -> bool {
+        match error {
+            EODHDError::Transport { source, .. } => {
+                self.retry_transport
+                    && (source.is_timeout() || source.is_connect() || source.is_request())
+            }
+            EODHDError::Status { status, .. } => self.retry_statuses.contains(status),
+            EODHDError::RateLimited { .. } => self.retry_statuses.contains(&429),
+            _ => false,
+        }
+    }
+
+    /// Delay before the retry following the failed `attempt` (0 based)
+    pub(crate) fn delay(&self, attempt: u32, error: &EODHDError) -> Duration {
+        if self.respect_retry_after {
+            if let EODHDError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } = error
+            {
+                return (*retry_after).min(self.max_delay);
+            }
+        }
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        if self.jitter {
+            backoff.mul_f64(random_fraction())
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Parses the `Retry-After` header, which is
+/// either delay-seconds or an HTTP-date.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+/// Random number in [0, 1) without pulling in a rng,
+/// std's `RandomState` is seeded randomly per instance.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    hasher.write_u128(nanos);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_limit_is_not_retried() {
+        let policy = EODHDRetryPolicy::default();
+        assert!(policy.is_retryable(&EODHDError::RateLimited {
+            url: String::new(),
+            body: String::new(),
+            retry_after: None,
+        }));
+        assert!(!policy.is_retryable(&EODHDError::DailyLimitReached {
+            url: String::new(),
+            body: String::new(),
+        }));
+    }
+}
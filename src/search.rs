@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    datetime::eodhd_serde_opt_date,
+    eodhd_opt_string_float,
+    rate_limit::EODHDEndpoint,
+    ticker::{encode_path_segment, EODHDTicker},
+    EODHDClient, EODHDError,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDSearchResult {
+    /// without the exchange suffix
+    #[serde(rename = "Code")]
+    pub code: String,
+    #[serde(rename = "Exchange")]
+    pub exchange: String,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    /// e.g. "Common Stock", "ETF", "FUND"
+    #[serde(rename = "Type")]
+    pub type_symbol: Option<String>,
+    #[serde(rename = "Country")]
+    pub country: Option<String>,
+    #[serde(rename = "Currency")]
+    pub currency: Option<String>,
+    #[serde(rename = "ISIN")]
+    pub isin: Option<String>,
+    #[serde(
+        rename = "previousClose",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub previous_close: Option<f64>,
+    #[serde(rename = "previousCloseDate", default, with = "eodhd_serde_opt_date")]
+    pub previous_close_date: Option<NaiveDate>,
+    #[serde(rename = "isPrimary", default)]
+    pub is_primary: bool,
+}
+
+impl EODHDSearchResult {
+    pub fn ticker(&self) -> EODHDTicker {
+        EODHDTicker::new(&self.code, &self.exchange)
+    }
+}
+
+/// Asset types the search can be limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EODHDSearchType {
+    All,
+    Stock,
+    ETF,
+    Fund,
+    Bonds,
+    Index,
+    Crypto,
+}
+
+impl Display for EODHDSearchType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EODHDSearchType::All => "all",
+            EODHDSearchType::Stock => "stock",
+            EODHDSearchType::ETF => "etf",
+            EODHDSearchType::Fund => "fund",
+            EODHDSearchType::Bonds => "bonds",
+            EODHDSearchType::Index => "index",
+            EODHDSearchType::Crypto => "crypto",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EODHDSearchFilter {
+    // Amount of results, if not provided 15 (at most 500).
+    pub limit: Option<u32>,
+    // If not provided all types except bonds.
+    pub type_search: Option<EODHDSearchType>,
+    // Exchange code like "US" or "XETRA".
+    pub exchange: Option<String>,
+}
+
+/// The identifiers that can be mapped to EODHD tickers
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EODHDId {
+    Isin(String),
+    Cusip(String),
+    Figi(String),
+    Lei(String),
+}
+
+impl EODHDId {
+    fn filter_key(&self) -> &'static str {
+        match self {
+            EODHDId::Isin(_) => "filter[isin]",
+            EODHDId::Cusip(_) => "filter[cusip]",
+            EODHDId::Figi(_) => "filter[figi]",
+            EODHDId::Lei(_) => "filter[lei]",
+        }
+    }
+
+    fn value(&self) -> &str {
+        match self {
+            EODHDId::Isin(v) | EODHDId::Cusip(v) | EODHDId::Figi(v) | EODHDId::Lei(v) => v,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDIdMapping {
+    /// ticker with exchange suffix, e.g. "AAPL.US"
+    pub symbol: String,
+    pub isin: Option<String>,
+    pub figi: Option<String>,
+    pub lei: Option<String>,
+    pub cusip: Option<String>,
+    pub cik: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IdMappingResponse {
+    #[serde(default)]
+    data: Vec<EODHDIdMapping>,
+}
+
+impl EODHDClient {
+    /// Finds tickers by code, name or ISIN
+    pub async fn search(
+        &self,
+        query: &str,
+        filter: Option<EODHDSearchFilter>,
+    ) -> Result<Vec<EODHDSearchResult>, EODHDError> {
+        let mut params = vec![];
+        if let Some(filter) = filter {
+            if let Some(limit) = filter.limit {
+                params.push(("limit", limit.to_string()));
+            }
+            if let Some(type_search) = filter.type_search {
+                params.push(("type", type_search.to_string()));
+                if type_search == EODHDSearchType::Bonds {
+                    params.push(("bonds_only", "1".to_string()));
+                }
+            }
+            if let Some(exchange) = filter.exchange {
+                params.push(("exchange", exchange));
+            }
+        }
+        self.get_json(
+            EODHDEndpoint::Search,
+            &format!("search/{}", encode_path_segment(query)),
+            &params,
+        )
+        .await
+    }
+
+    /// All listings EODHD knows for `id`, uncached,
+    /// see [EODHDIdResolver] for repeated lookups
+    pub async fn get_id_mapping(&self, id: &EODHDId) -> Result<Vec<EODHDIdMapping>, EODHDError> {
+        let response: IdMappingResponse = self
+            .get_json(
+                EODHDEndpoint::IdMapping,
+                "id-mapping",
+                &[(id.filter_key(), id.value().to_string())],
+            )
+            .await?;
+        Ok(response.data)
+    }
+}
+
+/// Shorthand for [EODHDClient::search]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn search(
+    query: &str,
+    filter: Option<EODHDSearchFilter>,
+) -> Result<Vec<EODHDSearchResult>, EODHDError> {
+    EODHDClient::from_env()?.search(query, filter).await
+}
+
+/// Resolves ISIN, CUSIP, FIGI or LEI to EODHD tickers,
+/// remembering the answers (including unknown ids)
+/// for the lifetime of the resolver.
+#[derive(Debug)]
+pub struct EODHDIdResolver {
+    client: EODHDClient,
+    cache: Mutex<HashMap<EODHDId, Vec<EODHDIdMapping>>>,
+}
+
+impl EODHDIdResolver {
+    pub fn new(client: EODHDClient) -> Self {
+        Self {
+            client,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// All listings of `id`, empty if unknown
+    pub async fn resolve_all(&self, id: &EODHDId) -> Result<Vec<EODHDIdMapping>, EODHDError> {
+        if let Some(mappings) = self.cache().get(id) {
+            return Ok(mappings.clone());
+        }
+        let mappings = self.client.get_id_mapping(id).await?;
+        self.cache().insert(id.clone(), mappings.clone());
+        Ok(mappings)
+    }
+
+    /// The ticker of the first listing of `id`, preferring
+    /// one on `exchange` if given (e.g. "US" or "XETRA")
+    pub async fn resolve(
+        &self,
+        id: &EODHDId,
+        exchange: Option<&str>,
+    ) -> Result<Option<EODHDTicker>, EODHDError> {
+        let tickers = self
+            .resolve_all(id)
+            .await?
+            .iter()
+            .filter_map(|m| m.symbol.parse::<EODHDTicker>().ok())
+            .collect::<Vec<EODHDTicker>>();
+        let preferred = exchange.and_then(|exchange| {
+            tickers
+                .iter()
+                .find(|t| t.exchange.eq_ignore_ascii_case(exchange))
+        });
+        Ok(preferred.or(tickers.first()).cloned())
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache().clear();
+    }
+
+    /// Entries are only ever inserted whole,
+    /// so a poisoned lock is still usable
+    fn cache(&self) -> MutexGuard<'_, HashMap<EODHDId, Vec<EODHDIdMapping>>> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(symbol: &str) -> EODHDIdMapping {
+        EODHDIdMapping {
+            symbol: symbol.to_string(),
+            isin: Some("US0378331005".to_string()),
+            figi: None,
+            lei: None,
+            cusip: None,
+            cik: None,
+        }
+    }
+
+    /// A resolver whose requests fail, answers can only come from the cache
+    fn offline_resolver() -> EODHDIdResolver {
+        EODHDIdResolver::new(EODHDClient::new("token").with_base_url("http://127.0.0.1:9"))
+    }
+
+    #[test]
+    fn parses_search_results() {
+        let results: Vec<EODHDSearchResult> = serde_json::from_str(
+            r#"[{"Code":"AAPL","Exchange":"US","Name":"Apple Inc","Type":"Common Stock","Country":"USA","Currency":"USD","ISIN":"US0378331005","previousClose":"189.95","previousCloseDate":"2023-12-29","isPrimary":true}]"#,
+        )
+        .unwrap();
+        assert_eq!(results[0].ticker(), EODHDTicker::new("AAPL", "US"));
+        assert_eq!(results[0].previous_close, Some(189.95));
+        assert_eq!(
+            results[0].previous_close_date,
+            NaiveDate::from_ymd_opt(2023, 12, 29)
+        );
+        assert!(results[0].is_primary);
+    }
+
+    #[test]
+    fn parses_id_mappings() {
+        let response: IdMappingResponse = serde_json::from_str(
+            r#"{"meta":{"total":1},"data":[{"symbol":"AAPL.US","isin":"US0378331005","figi":null,"lei":null,"cusip":"037833100","cik":"0000320193"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(response.data[0].symbol, "AAPL.US");
+        assert_eq!(response.data[0].cusip.as_deref(), Some("037833100"));
+
+        let empty: IdMappingResponse = serde_json::from_str(r#"{"meta":{"total":0}}"#).unwrap();
+        assert!(empty.data.is_empty());
+
+        let id = EODHDId::Cusip("037833100".to_string());
+        assert_eq!(
+            (id.filter_key(), id.value()),
+            ("filter[cusip]", "037833100")
+        );
+    }
+
+    #[tokio::test]
+    async fn resolver_answers_from_the_cache() {
+        let resolver = offline_resolver();
+        let apple = EODHDId::Isin("US0378331005".to_string());
+        let unknown = EODHDId::Isin("XX0000000000".to_string());
+        resolver.cache().insert(
+            apple.clone(),
+            vec![mapping("AAPL.US"), mapping("APC.XETRA")],
+        );
+        resolver.cache().insert(unknown.clone(), vec![]);
+
+        assert_eq!(resolver.resolve_all(&apple).await.unwrap().len(), 2);
+        assert_eq!(
+            resolver.resolve(&apple, None).await.unwrap(),
+            Some(EODHDTicker::new("AAPL", "US"))
+        );
+        assert_eq!(
+            resolver.resolve(&apple, Some("xetra")).await.unwrap(),
+            Some(EODHDTicker::new("APC", "XETRA"))
+        );
+        // unknown ids are remembered too
+        assert_eq!(resolver.resolve(&unknown, None).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn clear_cache_requests_again() {
+        let resolver = offline_resolver();
+        let apple = EODHDId::Isin("US0378331005".to_string());
+        resolver
+            .cache()
+            .insert(apple.clone(), vec![mapping("AAPL.US")]);
+        assert!(resolver.resolve_all(&apple).await.is_ok());
+
+        resolver.clear_cache();
+        assert!(resolver.cache().is_empty());
+        assert!(resolver.resolve_all(&apple).await.is_err());
+    }
+
+    #[test]
+    fn poisoned_cache_is_still_usable() {
+        let resolver = offline_resolver();
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let _cache = resolver.cache();
+                    panic!("poisoning the cache");
+                })
+                .join()
+                .unwrap_err();
+        });
+        assert!(resolver.cache.is_poisoned());
+        resolver.clear_cache();
+        assert!(resolver.cache().is_empty());
+    }
+}
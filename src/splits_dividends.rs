@@ -0,0 +1,312 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    datetime::eodhd_serde_opt_date,
+    end_of_period::{EODHDAdjustedEndOfPeriod, EODHDEndOfPeriod},
+    eodhd_opt_string_float,
+    rate_limit::EODHDEndpoint,
+    ticker::{parse_ticker, EODHDTicker},
+    EODHDClient, EODHDError,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDDividend {
+    /// ex dividend date
+    pub date: NaiveDate,
+    #[serde(rename = "declarationDate", default, with = "eodhd_serde_opt_date")]
+    pub declaration_date: Option<NaiveDate>,
+    #[serde(rename = "recordDate", default, with = "eodhd_serde_opt_date")]
+    pub record_date: Option<NaiveDate>,
+    #[serde(rename = "paymentDate", default, with = "eodhd_serde_opt_date")]
+    pub payment_date: Option<NaiveDate>,
+    pub period: Option<String>,
+    /// adjusted for later splits
+    #[serde(default, deserialize_with = "eodhd_opt_string_float::deserialize")]
+    pub value: Option<f64>,
+    /// as paid at the time
+    #[serde(
+        rename = "unadjustedValue",
+        default,
+        deserialize_with = "eodhd_opt_string_float::deserialize"
+    )]
+    pub unadjusted_value: Option<f64>,
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EODHDSplit {
+    pub date: NaiveDate,
+    pub split: EODHDSplitRatio,
+}
+
+/// A split of `old` shares into `new` shares,
+/// EODHD encodes it as "2.000000/1.000000"
+/// (sometimes "2:1").
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EODHDSplitRatio {
+    pub new: f64,
+    pub old: f64,
+}
+
+impl EODHDSplitRatio {
+    /// Shares after the split per share before,
+    /// prices before the split are divided by it
+    pub fn factor(&self) -> f64 {
+        self.new / self.old
+    }
+}
+
+impl FromStr for EODHDSplitRatio {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (new, old) = s
+            .split_once('/')
+            .or_else(|| s.split_once(':'))
+            .ok_or_else(|| format!("faulty eodhd split ratio {:?}", s))?;
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<f64>()
+                .map_err(|e| format!("faulty eodhd split ratio {:?}: {}", s, e))
+        };
+        let ratio = EODHDSplitRatio {
+            new: parse(new)?,
+            old: parse(old)?,
+        };
+        if ratio.new <= 0.0 || ratio.old <= 0.0 {
+            return Err(format!("faulty eodhd split ratio {:?}", s));
+        }
+        Ok(ratio)
+    }
+}
+
+impl Display for EODHDSplitRatio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.6}/{:.6}", self.new, self.old)
+    }
+}
+
+impl Serialize for EODHDSplitRatio {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for EODHDSplitRatio {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+pub struct EODHDSplitsDividendsFilter {
+    // The start date, if not provided the whole history is used.
+    pub from: Option<chrono::NaiveDate>,
+    // The end date, if not provided, today will be used.
+    pub to: Option<chrono::NaiveDate>,
+}
+
+fn query(filter: Option<EODHDSplitsDividendsFilter>) -> Vec<(&'static str, String)> {
+    let mut query = vec![];
+    if let Some(filter) = filter {
+        if let Some(from) = filter.from {
+            query.push(("from", from.format("%Y-%m-%d").to_string()));
+        }
+        if let Some(to) = filter.to {
+            query.push(("to", to.format("%Y-%m-%d").to_string()));
+        }
+    }
+    query
+}
+
+impl EODHDClient {
+    pub async fn get_dividends(
+        &self,
+        ticker: &EODHDTicker,
+        filter: Option<EODHDSplitsDividendsFilter>,
+    ) -> Result<Vec<EODHDDividend>, EODHDError> {
+        self.get_ticker_json(
+            EODHDEndpoint::Dividends,
+            ticker,
+            &format!("div/{}", ticker.path_segment()),
+            &query(filter),
+        )
+        .await
+    }
+
+    pub async fn get_splits(
+        &self,
+        ticker: &EODHDTicker,
+        filter: Option<EODHDSplitsDividendsFilter>,
+    ) -> Result<Vec<EODHDSplit>, EODHDError> {
+        self.get_ticker_json(
+            EODHDEndpoint::Splits,
+            ticker,
+            &format!("splits/{}", ticker.path_segment()),
+            &query(filter),
+        )
+        .await
+    }
+}
+
+/// Shorthand for [EODHDClient::get_dividends]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_dividends(
+    ticker: &str,
+    filter: Option<EODHDSplitsDividendsFilter>,
+) -> Result<Vec<EODHDDividend>, EODHDError> {
+    EODHDClient::from_env()?
+        .get_dividends(&parse_ticker(ticker)?, filter)
+        .await
+}
+
+/// Shorthand for [EODHDClient::get_splits]
+/// with a client created from `EODHD_TOKEN`.
+pub async fn get_splits(
+    ticker: &str,
+    filter: Option<EODHDSplitsDividendsFilter>,
+) -> Result<Vec<EODHDSplit>, EODHDError> {
+    EODHDClient::from_env()?
+        .get_splits(&parse_ticker(ticker)?, filter)
+        .await
+}
+
+/// Recomputes split and dividend adjusted OHLCV from the
+/// unadjusted values of `bars`, the way EODHD computes
+/// `adjusted_close` (so it can be verified against `raw.c`).
+/// Walking back from the latest bar, prices before a split
+/// are divided by its ratio (volumes multiplied) and prices
+/// before an ex dividend date are multiplied by
+/// `1 - dividend / previous close`. Events between two
+/// trading days count for the later one. The result is
+/// sorted by date (oldest first).
+pub fn adjust_end_of_period(
+    bars: &[EODHDEndOfPeriod],
+    splits: &[EODHDSplit],
+    dividends: &[EODHDDividend],
+) -> Vec<EODHDAdjustedEndOfPeriod> {
+    let mut bars = bars.to_vec();
+    bars.sort_by_key(|bar| bar.date);
+
+    let mut price_factor = 1.0;
+    let mut volume_factor = 1.0;
+    let mut adjusted = Vec::with_capacity(bars.len());
+    for i in (0..bars.len()).rev() {
+        let bar = bars[i];
+        adjusted.push(EODHDAdjustedEndOfPeriod::scaled(
+            bar,
+            price_factor,
+            volume_factor,
+        ));
+
+        if i == 0 {
+            break;
+        }
+        let previous = bars[i - 1];
+        let between = |date: NaiveDate| previous.date < date && date <= bar.date;
+        for split in splits.iter().filter(|s| between(s.date)) {
+            price_factor /= split.split.factor();
+            volume_factor *= split.split.factor();
+        }
+        for dividend in dividends.iter().filter(|d| between(d.date)) {
+            // the close before the ex date is unadjusted, so is the dividend needed
+            let value = dividend.unadjusted_value.or(dividend.value).unwrap_or(0.0);
+            if previous.unadjusted_close > 0.0 {
+                price_factor *= 1.0 - value / previous.unadjusted_close;
+            }
+        }
+    }
+    adjusted.reverse();
+    adjusted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 3, day).unwrap()
+    }
+
+    fn bar(day: u32, unadjusted_close: f64) -> EODHDEndOfPeriod {
+        EODHDEndOfPeriod {
+            date: date(day),
+            o: unadjusted_close,
+            h: unadjusted_close,
+            l: unadjusted_close,
+            unadjusted_close,
+            c: unadjusted_close,
+            v: 1000,
+        }
+    }
+
+    fn split(day: u32) -> EODHDSplit {
+        EODHDSplit {
+            date: date(day),
+            split: "2.000000/1.000000".parse().unwrap(),
+        }
+    }
+
+    fn dividend(day: u32, unadjusted_value: f64) -> EODHDDividend {
+        EODHDDividend {
+            date: date(day),
+            declaration_date: None,
+            record_date: None,
+            payment_date: None,
+            period: None,
+            value: Some(unadjusted_value / 2.0),
+            unadjusted_value: Some(unadjusted_value),
+            currency: None,
+        }
+    }
+
+    #[test]
+    fn split_ratios() {
+        let ratio = "2.000000/1.000000".parse::<EODHDSplitRatio>().unwrap();
+        assert_eq!(ratio, EODHDSplitRatio { new: 2.0, old: 1.0 });
+        assert_eq!(ratio.to_string(), "2.000000/1.000000");
+        assert_eq!("1:4".parse::<EODHDSplitRatio>().unwrap().factor(), 0.25);
+        for faulty in ["2", "a/1", "0/1", "1/-2"] {
+            assert!(faulty.parse::<EODHDSplitRatio>().is_err(), "{}", faulty);
+        }
+        let split: EODHDSplit =
+            serde_json::from_str(r#"{"date": "2020-08-31", "split": "4.000000/1.000000"}"#)
+                .unwrap();
+        assert_eq!(split.split.factor(), 4.0);
+    }
+
+    #[test]
+    fn split_and_dividend_on_the_same_day() {
+        let bars = [bar(2, 52.0), bar(1, 100.0)];
+        let adjusted = adjust_end_of_period(&bars, &[split(2)], &[dividend(2, 1.0)]);
+        assert_eq!(adjusted.len(), 2);
+        assert_eq!(adjusted[0].date, date(1));
+        // halved by the split, then 1 - 1 / 100 for the dividend
+        assert!((adjusted[0].factor - 0.495).abs() < 1e-12);
+        assert!((adjusted[0].c - 49.5).abs() < 1e-9);
+        assert_eq!(adjusted[0].v, 2000.0);
+        assert_eq!(adjusted[1].factor, 1.0);
+        assert_eq!(adjusted[1].c, 52.0);
+    }
+
+    #[test]
+    fn events_count_for_the_next_trading_day() {
+        // split on a weekend between the bars
+        let bars = [bar(3, 100.0), bar(6, 50.0)];
+        let adjusted = adjust_end_of_period(&bars, &[split(4)], &[]);
+        assert_eq!(adjusted[0].factor, 0.5);
+        assert_eq!(adjusted[1].factor, 1.0);
+    }
+
+    #[test]
+    fn dividend_after_a_missing_close_is_skipped() {
+        let bars = [bar(1, 100.0), bar(2, f64::NAN), bar(3, 50.0)];
+        let adjusted = adjust_end_of_period(&bars, &[], &[dividend(3, 1.0)]);
+        assert!(adjusted.iter().all(|bar| bar.factor == 1.0));
+        assert_eq!(adjusted[0].c, 100.0);
+        assert!(adjusted[1].c.is_nan());
+    }
+}
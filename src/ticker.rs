@@ -0,0 +1,238 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::EODHDError;
+
+/// Exchanges EODHD uses for instruments
+/// that aren't listed on a single venue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EODHDVirtualExchange {
+    /// all US exchanges combined (NYSE, NASDAQ, ...)
+    US,
+    FOREX,
+    /// crypto currencies
+    CC,
+    /// indices
+    INDX,
+    /// government bonds
+    GBOND,
+    /// money market rates
+    MONEY,
+}
+
+impl EODHDVirtualExchange {
+    /// Timezone EODHD dates the instruments in,
+    /// `None` where it differs by instrument
+    pub fn timezone(&self) -> Option<Tz> {
+        match self {
+            EODHDVirtualExchange::US => Some(Tz::America__New_York),
+            EODHDVirtualExchange::FOREX | EODHDVirtualExchange::CC => Some(Tz::UTC),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            EODHDVirtualExchange::US => "US",
+            EODHDVirtualExchange::FOREX => "FOREX",
+            EODHDVirtualExchange::CC => "CC",
+            EODHDVirtualExchange::INDX => "INDX",
+            EODHDVirtualExchange::GBOND => "GBOND",
+            EODHDVirtualExchange::MONEY => "MONEY",
+        }
+    }
+}
+
+impl FromStr for EODHDVirtualExchange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "US" => Ok(EODHDVirtualExchange::US),
+            "FOREX" => Ok(EODHDVirtualExchange::FOREX),
+            "CC" => Ok(EODHDVirtualExchange::CC),
+            "INDX" => Ok(EODHDVirtualExchange::INDX),
+            "GBOND" => Ok(EODHDVirtualExchange::GBOND),
+            "MONEY" => Ok(EODHDVirtualExchange::MONEY),
+            _ => Err(format!("{:?} is not a virtual eodhd exchange", s)),
+        }
+    }
+}
+
+impl Display for EODHDVirtualExchange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// A ticker as EODHD expects it, `{code}.{exchange}`,
+/// e.g. "AAPL.US", "EURUSD.FOREX" or "BTC-USD.CC".
+/// Parsing requires the exchange suffix, the code itself
+/// may contain dots ("BRK.B.US" is code "BRK.B").
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EODHDTicker {
+    pub code: String,
+    /// exchange code, always upper case
+    pub exchange: String,
+}
+
+impl EODHDTicker {
+    pub fn new(code: &str, exchange: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            exchange: exchange.to_ascii_uppercase(),
+        }
+    }
+
+    pub fn us(code: &str) -> Self {
+        Self::new(code, EODHDVirtualExchange::US.code())
+    }
+
+    /// A currency pair like "EURUSD"
+    pub fn forex(pair: &str) -> Self {
+        Self::new(pair, EODHDVirtualExchange::FOREX.code())
+    }
+
+    /// A crypto pair like "BTC-USD"
+    pub fn crypto(pair: &str) -> Self {
+        Self::new(pair, EODHDVirtualExchange::CC.code())
+    }
+
+    pub fn virtual_exchange(&self) -> Option<EODHDVirtualExchange> {
+        self.exchange.parse().ok()
+    }
+
+    /// The ticker percent encoded for use as url path segment
+    pub fn path_segment(&self) -> String {
+        encode_path_segment(&self.to_string())
+    }
+
+    /// The format the realtime sockets expect
+    /// in `subscribe`, the code without suffix
+    pub fn socket_symbol(&self) -> &str {
+        &self.code
+    }
+}
+
+impl FromStr for EODHDTicker {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (code, exchange) = s
+            .rsplit_once('.')
+            .ok_or_else(|| format!("eodhd ticker {:?} lacks an exchange suffix like \".US\"", s))?;
+        if code.is_empty() || exchange.is_empty() {
+            return Err(format!("faulty eodhd ticker {:?}", s));
+        }
+        if !exchange.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!(
+                "faulty exchange {:?} in eodhd ticker {:?}",
+                exchange, s
+            ));
+        }
+        Ok(EODHDTicker::new(code, exchange))
+    }
+}
+
+impl Display for EODHDTicker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.code, self.exchange)
+    }
+}
+
+impl Serialize for EODHDTicker {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for EODHDTicker {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parses the tickers given to the free functions
+pub(crate) fn parse_ticker(ticker: &str) -> Result<EODHDTicker, EODHDError> {
+    ticker.parse().map_err(|reason| EODHDError::InvalidTicker {
+        ticker: ticker.to_string(),
+        reason,
+    })
+}
+
+/// Percent encodes `/`, `?`, `#` and the like
+/// so `segment` stays a single path segment
+pub(crate) fn encode_path_segment(segment: &str) -> String {
+    let mut url = url::Url::parse("http://localhost/").unwrap();
+    url.path_segments_mut().unwrap().clear().push(segment);
+    url.path().trim_start_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        for ticker in [
+            "AAPL.US",
+            "BRK.B.US",
+            "EURUSD.FOREX",
+            "BTC-USD.CC",
+            "GSPC.INDX",
+        ] {
+            assert_eq!(ticker.parse::<EODHDTicker>().unwrap().to_string(), ticker);
+        }
+        let ticker = "BRK.B.US".parse::<EODHDTicker>().unwrap();
+        assert_eq!(ticker, EODHDTicker::us("BRK.B"));
+        assert_eq!(
+            " vod.lse ".parse::<EODHDTicker>().unwrap(),
+            EODHDTicker::new("vod", "LSE")
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        for ticker in ["AAPL", ".US", "AAPL.", "AAPL.U-S"] {
+            assert!(ticker.parse::<EODHDTicker>().is_err(), "{}", ticker);
+        }
+        match parse_ticker("AAPL") {
+            Err(EODHDError::InvalidTicker { ticker, .. }) => assert_eq!(ticker, "AAPL"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn virtual_exchanges() {
+        assert_eq!(
+            EODHDTicker::crypto("BTC-USD").virtual_exchange(),
+            Some(EODHDVirtualExchange::CC)
+        );
+        assert_eq!(EODHDTicker::new("VOD", "LSE").virtual_exchange(), None);
+        assert_eq!("forex".parse(), Ok(EODHDVirtualExchange::FOREX));
+        assert_eq!(
+            EODHDVirtualExchange::US.timezone(),
+            Some(Tz::America__New_York)
+        );
+    }
+
+    #[test]
+    fn path_segment_and_socket_symbol() {
+        assert_eq!(EODHDTicker::us("BRK.B").path_segment(), "BRK.B.US");
+        assert_eq!(EODHDTicker::new("A/B", "XX").path_segment(), "A%2FB.XX");
+        assert_eq!(EODHDTicker::forex("EURUSD").socket_symbol(), "EURUSD");
+    }
+
+    #[test]
+    fn serde_as_string() {
+        let ticker = EODHDTicker::us("AAPL");
+        let json = serde_json::to_string(&ticker).unwrap();
+        assert_eq!(json, r#""AAPL.US""#);
+        assert_eq!(serde_json::from_str::<EODHDTicker>(&json).unwrap(), ticker);
+        assert!(serde_json::from_str::<EODHDTicker>(r#""AAPL""#).is_err());
+    }
+}